@@ -1,15 +1,21 @@
 use crate::config::Config;
 use crate::item::Item;
-use crate::store::{inner::ItemView, RecState, Store, MAX_DEPTH};
+use crate::report::{Report, ReportBy};
+use crate::selector::SelectorArgs;
+use crate::store::{inner::ItemView, RecState, Store};
 use crate::view;
 use crate::Selector;
 use crate::{git, util};
 use std::error::Error;
 use std::fs::{self, File};
+use std::io::{stdin, stdout, IsTerminal, Write};
 use std::path::Path;
 use std::process::Command;
 use termimad::crossterm::style::Stylize;
 
+/// separates the ids of an ancestor path
+const PATH_SEPARATOR: &str = " › ";
+
 pub fn add<F>(
     debug: F,
     config: &Config,
@@ -61,121 +67,567 @@ where
     } else {
         store.add(item)?;
     }
-    view::print(&config, store.get_item(&_id).unwrap().print_long(false))?;
+    view::print(
+        config,
+        store
+            .get_item(&_id)
+            .unwrap()
+            .print_long(false, store.rollup(&_id).as_ref()),
+    )?;
     Ok(())
 }
 
-pub fn remove<F>(debug: F, store: &mut Store, selector: Selector) -> Result<(), Box<dyn Error>>
+pub fn remove<F>(
+    debug: F,
+    config: &Config,
+    store: &mut Store,
+    selector: Selector,
+    yes: bool,
+) -> Result<(), Box<dyn Error>>
 where
     F: Fn(&str),
 {
     debug(&format!("remove {:?}", selector));
+    // an empty selector matches every item, only exclusions are explicit enough
+    if selector.is_empty() && !selector.has_exclusions() {
+        return Err("Refusing to remove items without a selector.".into());
+    }
     let ids = selector.get(store, true);
+    if selector.is_empty() {
+        // everything but the exclusions, asked regardless of --yes and the confirm_threshold
+        if !stdin().is_terminal() {
+            return Err(
+                "Removing all items but the excluded ones needs a terminal to confirm.".into(),
+            );
+        }
+        ask(store, "Remove", &ids)?;
+    } else {
+        confirm(config, store, "Remove", &ids, yes)?;
+    }
     for id in &ids {
         store.remove(id)?;
     }
-    println!("{} removed.", ids.len());
+    done(config, &format!("{} removed.", ids.len()));
     Ok(())
 }
 
 pub fn tag<F>(
     debug: F,
+    config: &Config,
     store: &mut Store,
     selector: Selector,
     tags: &Option<String>,
+    yes: bool,
 ) -> Result<(), Box<dyn Error>>
 where
     F: Fn(&str),
 {
     debug(&format!("tag {:?} new_tags {:?}", selector, tags));
     let ids = selector.get(store, true);
+    confirm(config, store, "Tag", &ids, yes)?;
     for id in &ids {
         store
             .get_item_mut(id)
             .unwrap()
-            .append_tags(util::split_comma_tags(
+            .append_tags(util::split_comma_include(
                 tags.to_owned().unwrap_or("".to_string()),
             ))
-            .remove_tags(util::split_comma_exclude_tags(
+            .remove_tags(util::split_comma_exclude(
                 tags.to_owned().unwrap_or("".to_string()),
             ));
     }
-    println!("{} tagged.", ids.len());
+    done(config, &format!("{} tagged.", ids.len()));
     Ok(())
 }
 
-pub fn start<F>(debug: F, store: &mut Store, selector: Selector) -> Result<(), Box<dyn Error>>
+pub fn tags_list<F>(debug: F, store: &Store) -> Result<(), Box<dyn Error>>
 where
     F: Fn(&str),
 {
-    debug(&format!("start {:?}", selector));
-    let ids = selector.get(store, true);
+    let usage = store.tag_usage();
+    debug(&format!("tags list {:?}", usage));
+    if usage.is_empty() {
+        println!("No tags.");
+    }
+    let max_len = usage.keys().map(|t| t.chars().count()).max().unwrap_or(0);
+    for (tag, (count, last_modified)) in &usage {
+        println!(
+            "{}{} {:>4}  {}",
+            tag.to_string().yellow(),
+            " ".repeat(max_len - tag.chars().count()),
+            count,
+            util::format_timestamp(*last_modified).grey()
+        );
+    }
+    Ok(())
+}
+
+pub fn tags_rename<F>(
+    debug: F,
+    config: &Config,
+    store: &mut Store,
+    old: &str,
+    new: &str,
+    yes: bool,
+) -> Result<(), Box<dyn Error>>
+where
+    F: Fn(&str),
+{
+    debug(&format!("tags rename {:?} to {:?}", old, new));
+    let new = check_tag(new)?;
+    if store.get().values().any(|item| item.has_tag(&new)) {
+        return Err(format!("Tag '{}' is already used, see cake tags merge", new).into());
+    }
+    confirm(config, store, "Retag", &tagged(store, old), yes)?;
+    let count = store.rename_tag(old, Some(&new));
+    if count == 0 {
+        return Err(format!("Could not found tag '{}'", old).into());
+    }
+    done(config, &format!("{} renamed.", count));
+    Ok(())
+}
+
+pub fn tags_merge<F>(
+    debug: F,
+    config: &Config,
+    store: &mut Store,
+    from: &str,
+    into: &str,
+    yes: bool,
+) -> Result<(), Box<dyn Error>>
+where
+    F: Fn(&str),
+{
+    debug(&format!("tags merge {:?} into {:?}", from, into));
+    let into = check_tag(into)?;
+    if !store.get().values().any(|item| item.has_tag(&into)) {
+        return Err(format!("Could not found tag '{}', see cake tags rename", into).into());
+    }
+    confirm(config, store, "Retag", &tagged(store, from), yes)?;
+    let count = store.rename_tag(from, Some(&into));
+    if count == 0 {
+        return Err(format!("Could not found tag '{}'", from).into());
+    }
+    done(config, &format!("{} merged.", count));
+    Ok(())
+}
+
+pub fn tags_delete<F>(
+    debug: F,
+    config: &Config,
+    store: &mut Store,
+    tag: &str,
+    yes: bool,
+) -> Result<(), Box<dyn Error>>
+where
+    F: Fn(&str),
+{
+    debug(&format!("tags delete {:?}", tag));
+    confirm(config, store, "Untag", &tagged(store, tag), yes)?;
+    let count = store.rename_tag(tag, None);
+    if count == 0 {
+        return Err(format!("Could not found tag '{}'", tag).into());
+    }
+    done(config, &format!("{} untagged.", count));
+    Ok(())
+}
+
+/// ids of the items with the tag or a tag in its namespace
+fn tagged(store: &Store, tag: &str) -> Vec<String> {
+    let mut ids = store
+        .get()
+        .values()
+        .filter(|item| item.has_tag(tag))
+        .map(|item| item.id().to_string())
+        .collect::<Vec<String>>();
+    ids.sort();
+    ids
+}
+
+/// cleans a new tag name like cake tag does
+fn check_tag(tag: &str) -> Result<String, Box<dyn Error>> {
+    match util::split_comma_include(tag.to_string())[..] {
+        [ref t] => Ok(t.to_owned()),
+        _ => Err(format!("Invalid tag '{}'", tag).into()),
+    }
+}
+
+pub fn start<F>(
+    debug: F,
+    config: &Config,
+    store: &mut Store,
+    selector: Selector,
+    at: &[String],
+    switch: bool,
+) -> Result<(), Box<dyn Error>>
+where
+    F: Fn(&str),
+{
+    debug(&format!(
+        "start {:?} at {:?} switch {}",
+        selector, at, switch
+    ));
+    let at = parse_at(at)?;
+    let ids = pick_one(config, store, &selector, selector.get(store, true))?;
+    if switch || config.single_timer {
+        // the picker is skipped for recursive selections and without a terminal
+        if ids.len() > 1 {
+            return Err(format!(
+                "{} items selected, only one can run with --switch or single_timer.",
+                ids.len()
+            )
+            .into());
+        }
+        let mut running = store
+            .get()
+            .values()
+            .filter(|item| item.is_started() && !ids.contains(item.id()))
+            .map(|item| item.id().to_string())
+            .collect::<Vec<String>>();
+        running.sort();
+        for id in &running {
+            let item = store.get_item_mut(id).expect("Could not found id");
+            item.stop(at)?;
+            done(
+                config,
+                &format!("Stopped {} {}", id.to_string().yellow(), item.title()),
+            );
+        }
+    }
     for id in &ids {
         store
             .get_item_mut(id)
             .expect("Could not found id")
-            .start()?;
+            .start(at)?;
     }
-    println!("{} started.", ids.len());
+    done(config, &format!("{} started.", ids.len()));
     Ok(())
 }
 
-pub fn stop<F>(debug: F, store: &mut Store, selector: Selector) -> Result<(), Box<dyn Error>>
+/// prints the result message of a changing command,
+/// a dry run prints the diff of the items instead
+fn done(config: &Config, message: &str) {
+    if !config.dry_run {
+        println!("{}", message);
+    }
+}
+
+/// prints the added, changed and removed items between two versions of the store
+pub fn print_diff(before: &Store, after: &Store) {
+    let mut ids = before
+        .get()
+        .keys()
+        .chain(after.get().keys())
+        .collect::<Vec<&String>>();
+    ids.sort();
+    ids.dedup();
+    let mut changes = 0;
+    for id in ids {
+        match (before.get_item(id), after.get_item(id)) {
+            (None, Some(item)) => println!("{}", format!("+ {} {}", id, item.title()).green()),
+            (Some(item), None) => println!("{}", format!("- {} {}", id, item.title()).red()),
+            (Some(old), Some(new)) => {
+                let diff = old.diff(new);
+                if diff.is_empty() {
+                    continue;
+                }
+                println!("{}", format!("~ {} {}", id, new.title()).yellow());
+                for line in diff {
+                    println!("    {}", line);
+                }
+            }
+            (None, None) => continue,
+        }
+        changes += 1;
+    }
+    println!(
+        "{}",
+        format!("Dry run, {} items would change. Nothing written.", changes)
+            .grey()
+            .italic()
+    );
+}
+
+/// asks the user if more items than the confirm_threshold are affected,
+/// without a terminal to ask the command is refused
+fn confirm(
+    config: &Config,
+    store: &Store,
+    action: &str,
+    ids: &[String],
+    yes: bool,
+) -> Result<(), Box<dyn Error>> {
+    if yes || config.confirm_threshold < 0 || ids.len() <= config.confirm_threshold as usize {
+        return Ok(());
+    }
+    if !stdin().is_terminal() {
+        return Err(format!("{} items selected, use --yes to confirm.", ids.len()).into());
+    }
+    ask(store, action, ids)
+}
+
+/// lists the items and asks the user in the terminal
+fn ask(store: &Store, action: &str, ids: &[String]) -> Result<(), Box<dyn Error>> {
+    for id in ids {
+        let item = store.get_item(id).unwrap();
+        println!("{} {}", id.to_string().yellow(), item.title());
+    }
+    print!("{} {} items? [y/N] ", action, ids.len());
+    stdout().flush()?;
+    let mut answer = String::new();
+    stdin().read_line(&mut answer)?;
+    match answer.trim().to_lowercase().as_str() {
+        "y" | "yes" => Ok(()),
+        _ => Err("Aborted.".into()),
+    }
+}
+
+/// for single-target commands: if more items than the picker_threshold are selected,
+/// the user chooses one of them. Recursive selections are kept
+fn pick_one(
+    config: &Config,
+    store: &Store,
+    selector: &Selector,
+    ids: Vec<String>,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    if config.picker_threshold < 0
+        || ids.len() <= config.picker_threshold as usize
+        || selector.rchildren
+        || selector.rparents
+        || !stdout().is_terminal()
+    {
+        return Ok(ids);
+    }
+    let max_id_len = ids.iter().map(|id| id.len()).max().unwrap_or(0);
+    let lines = ids
+        .iter()
+        .map(|id| {
+            let item = store.get_item(id).unwrap();
+            format!("{:<width$} {}", id, item.title(), width = max_id_len)
+        })
+        .collect::<Vec<String>>();
+    let title = format!(
+        "{} items selected, pick one (enter) or abort (q):",
+        ids.len()
+    );
+    match view::pick(&title, &lines)? {
+        Some(i) => Ok(vec![ids[i].to_owned()]),
+        None => Err("Nothing picked.".into()),
+    }
+}
+
+pub fn stop<F>(
+    debug: F,
+    config: &Config,
+    store: &mut Store,
+    selector: Selector,
+    at: &[String],
+) -> Result<(), Box<dyn Error>>
 where
     F: Fn(&str),
 {
-    debug(&format!("stop {:?}", selector));
+    debug(&format!("stop {:?} at {:?}", selector, at));
+    let at = parse_at(at)?;
     let ids = selector.get(store, true);
     for id in &ids {
-        store.get_item_mut(id).expect("Could not found id").stop()?;
+        store
+            .get_item_mut(id)
+            .expect("Could not found id")
+            .stop(at)?;
     }
-    println!("{} stopped.", ids.len());
+    done(config, &format!("{} stopped.", ids.len()));
     Ok(())
 }
 
-pub fn list<F>(
+pub fn track<F>(
+    debug: F,
+    config: &Config,
+    store: &mut Store,
+    id: &str,
+    duration: &str,
+    at: &[String],
+) -> Result<(), Box<dyn Error>>
+where
+    F: Fn(&str),
+{
+    debug(&format!("track {:?} {:?} at {:?}", id, duration, at));
+    let duration = util::parse_duration(duration)?;
+    // without --at the interval ends now
+    let start = parse_at(at)?.unwrap_or(util::timestamp() - duration);
+    let item = store.get_item_mut(id).ok_or("Could not found id")?;
+    item.track(start, start + duration)?;
+    done(
+        config,
+        &format!(
+            "{} tracked for {} from {}.",
+            util::timestamp_to_hms(duration),
+            id,
+            util::format_timestamp(start)
+        ),
+    );
+    Ok(())
+}
+
+pub fn time_list<F>(debug: F, store: &Store, id: &str) -> Result<(), Box<dyn Error>>
+where
+    F: Fn(&str),
+{
+    let item = store.get_item(id).ok_or("Could not found id")?;
+    debug(&format!("time list {:?} {:?}", id, item.time_entries()));
+    if item.time_entries().is_empty() {
+        println!("No time tracked for {}.", id);
+    }
+    for (i, entry) in item.time_entries().iter().enumerate() {
+        println!(
+            "{:>3}  {} - {}  {:>8}  {}",
+            (i + 1).to_string().yellow(),
+            util::format_timestamp(entry.start),
+            match entry.end {
+                Some(end) => util::format_timestamp(end),
+                None => "running".to_string(),
+            },
+            util::timestamp_to_hms(entry.duration()),
+            entry.note.to_owned().grey()
+        );
+    }
+    Ok(())
+}
+
+pub fn time_edit<F>(
+    debug: F,
+    config: &Config,
+    store: &mut Store,
+    id: &str,
+    number: usize,
+    start: &[String],
+    end: &[String],
+) -> Result<(), Box<dyn Error>>
+where
+    F: Fn(&str),
+{
+    debug(&format!(
+        "time edit {:?} {:?} start: {:?} end: {:?}",
+        id, number, start, end
+    ));
+    let start = parse_at(start)?;
+    let end = parse_at(end)?;
+    if start.is_none() && end.is_none() {
+        return Err("Nothing to edit, use --start or --end.".into());
+    }
+    let item = store.get_item_mut(id).ok_or("Could not found id")?;
+    item.edit_time_entry(entry_index(number)?, start, end)?;
+    done(config, &format!("Time entry {} of {} edited.", number, id));
+    Ok(())
+}
+
+pub fn time_note<F>(
+    debug: F,
+    config: &Config,
+    store: &mut Store,
+    id: &str,
+    number: usize,
+    note: &str,
+) -> Result<(), Box<dyn Error>>
+where
+    F: Fn(&str),
+{
+    debug(&format!("time note {:?} {:?}: {:?}", id, number, note));
+    let item = store.get_item_mut(id).ok_or("Could not found id")?;
+    item.annotate_time_entry(entry_index(number)?, note)?;
+    done(
+        config,
+        &format!("Time entry {} of {} annotated.", number, id),
+    );
+    Ok(())
+}
+
+pub fn time_delete<F>(
     debug: F,
     config: &Config,
     store: &mut Store,
+    id: &str,
+    number: usize,
+) -> Result<(), Box<dyn Error>>
+where
+    F: Fn(&str),
+{
+    debug(&format!("time delete {:?} {:?}", id, number));
+    let item = store.get_item_mut(id).ok_or("Could not found id")?;
+    let entry = item.delete_time_entry(entry_index(number)?)?;
+    done(
+        config,
+        &format!("Time entry {} of {} deleted: {}", number, id, entry),
+    );
+    Ok(())
+}
+
+/// time entries are numbered from 1 for the user
+fn entry_index(number: usize) -> Result<usize, Box<dyn Error>> {
+    number
+        .checked_sub(1)
+        .ok_or_else(|| "Time entries are numbered from 1.".into())
+}
+
+/// time of --at, e.g. yesterday 14:00 given as multiple arguments
+fn parse_at(at: &[String]) -> Result<Option<i64>, Box<dyn Error>> {
+    Ok(util::parse_time_span(&at.join(" "))?.map(|(start, _)| start))
+}
+
+pub fn list<F>(
+    debug: F,
+    config: &Config,
+    store: &Store,
     selector: Selector,
     long: bool,
+    path: bool,
 ) -> Result<(), Box<dyn Error>>
 where
     F: Fn(&str),
 {
-    debug(&format!("list {:?} long: {:?}", selector, long));
+    debug(&format!(
+        "list {:?} long: {:?} path: {:?}",
+        selector, long, path
+    ));
 
-    let mut cycle: Vec<String> = vec![];
+    // excluded ids are marked as visited to prune the recursion there
+    let mut cycle: Vec<String> = selector.exclude_ids.clone();
     let item_views: Vec<ItemView>;
-    let max_depth = if selector.rchildren { MAX_DEPTH } else { 1 };
+    let max_depth = if selector.rchildren {
+        selector.max_depth
+    } else {
+        1
+    };
 
     // TODO recursive for both: rparents, rchildren
     // TODO shows same item as a child on -rrr
     let items = store.get();
+    // sorted by the given key, from old to new by default
     let mut keys = selector.get(store, false);
-    // sort output from old to new
-    keys.sort_by(|a, b| {
-        items
-            .get(a)
-            .unwrap()
-            .timestamp()
-            .cmp(&items.get(b).unwrap().timestamp())
-    });
-    // sort output by amount of parents. Zero parents first
-    keys.sort_by(|a, b| {
-        items
-            .get(a)
-            .unwrap()
-            .parents()
-            .len()
-            .cmp(&items.get(b).unwrap().parents().len())
-    });
+    // a tree starts with the roots: sort by amount of parents, stable to keep the order above.
+    // A flat list keeps the order of the selector
+    if selector.rchildren {
+        keys.sort_by(|a, b| {
+            items
+                .get(a)
+                .unwrap()
+                .parents()
+                .len()
+                .cmp(&items.get(b).unwrap().parents().len())
+        });
+    }
     // get the recursive ItemView to print with indention
     item_views = store
-        .recursive_execute(&keys, &mut cycle, 0, max_depth, selector.rparents)
+        .recursive_execute(
+            &keys,
+            &mut cycle,
+            0,
+            max_depth,
+            selector.rparents,
+            Some(&selector.sort),
+        )
         .iter()
         .filter(|iv| {
-            // filter exclude tags again, because recursive execution is not filtering in selector
+            // filter exclusions again, because recursive execution is not filtering in selector
             selector.exclude(iv.item.id(), store)
         })
         .map(|iv| iv.to_owned())
@@ -188,11 +640,22 @@ where
             if matches!(item_view.state, RecState::Reappearence) && item_view.depth > 0 {
                 text = text + &format!("{}", "### Recursion Warning ###".red());
             }
-            debug(&format!("### raw ###\n{}", item_view.item.print_long(true)));
+            debug(&format!(
+                "### raw ###\n{}",
+                item_view.item.print_long(true, None)
+            ));
+            if path {
+                for p in store.ancestor_paths(item_view.item.id()) {
+                    text = text + &format!("*{}*\n", p.join(PATH_SEPARATOR));
+                }
+            }
 
             // appends a dilimeter at the end if there are following items
             text = text
-                + &(item_view.item.to_string()
+                + &(item_view
+                    .item
+                    .print_long(false, store.rollup(item_view.item.id()).as_ref())
+                    + "\n"
                     + if i + 1 < item_views.len() {
                         "\n---\n"
                     } else {
@@ -208,33 +671,77 @@ where
                 b.item.id().len()
             }
         });
-        for item_view in item_views {
+        // box drawing connectors are only used for the children tree,
+        // with parent recursion the parents are listed above their children
+        let tree = selector.rchildren && !selector.rparents;
+        let prefixes =
+            util::tree_prefixes(&item_views.iter().map(|iv| iv.depth).collect::<Vec<_>>());
+        for (item_view, prefix) in item_views.iter().zip(prefixes) {
+            let indent = if tree && item_view.depth > 0 {
+                // the invisible mark keeps leading spaces from becoming a code block
+                format!("‎{}", prefix)
+            } else {
+                format!(
+                    "{:indent$} ",
+                    "‎",
+                    indent = if item_view.has_children {
+                        item_view.depth + 1
+                    } else {
+                        item_view.depth
+                    }
+                )
+            };
+            let ancestors = if path {
+                format!(
+                    " {}",
+                    store
+                        .ancestor_paths(item_view.item.id())
+                        .iter()
+                        .map(|p| p.join(PATH_SEPARATOR))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                        .grey()
+                )
+            } else {
+                "".to_string()
+            };
+            // the depth limit hides the subtree, show at least how big it is
+            let collapsed = if tree && item_view.has_children && item_view.depth + 1 == max_depth {
+                format!(
+                    " {}",
+                    format!("(+{})", store.descendants(item_view.item.id()).len()).grey()
+                )
+            } else {
+                "".to_string()
+            };
             match item_view.state {
                 RecState::Normal => {
                     text = text
                         + &format!(
-                            "{:indent$} {}\n",
-                            "‎",
-                            item_view.item.print(max_id_len, item_view.has_children),
-                            indent = if item_view.has_children {
-                                item_view.depth + 1
-                            } else {
-                                item_view.depth
-                            }
+                            "{}{}{}{}\n",
+                            indent,
+                            item_view.item.print(
+                                max_id_len,
+                                item_view.has_children,
+                                store.rollup(item_view.item.id()).as_ref(),
+                                selector.grep.as_ref()
+                            ),
+                            collapsed,
+                            ancestors
                         );
                     debug(&format!("indention {:?}", item_view.depth));
                 }
                 RecState::Reappearence if item_view.depth > 0 => {
                     text = text
                         + &format!(
-                            "{:indent$} {}",
-                            "‎",
-                            item_view.item.print(max_id_len, item_view.has_children),
-                            indent = if item_view.has_children {
-                                item_view.depth + 1
-                            } else {
-                                item_view.depth
-                            }
+                            "{}{}",
+                            indent,
+                            item_view.item.print(
+                                max_id_len,
+                                item_view.has_children,
+                                store.rollup(item_view.item.id()).as_ref(),
+                                selector.grep.as_ref()
+                            )
                         );
                     text = text + &format!(" {}\n", "### Reappearence Warning ###".red());
                     debug(&format!("indention {:?}", item_view.depth));
@@ -248,6 +755,168 @@ where
     Ok(())
 }
 
+pub fn report<F>(
+    debug: F,
+    config: &Config,
+    store: &Store,
+    selector: Selector,
+    (start, end): (i64, i64),
+    by: ReportBy,
+    csv: bool,
+) -> Result<(), Box<dyn Error>>
+where
+    F: Fn(&str),
+{
+    debug(&format!(
+        "report {:?} from {} to {} by {:?}",
+        selector, start, end, by
+    ));
+    let report = Report::new(store, &selector.get(store, true), by, start, end)?;
+    if report.is_empty() {
+        println!(
+            "No time tracked between {} and {}.",
+            util::format_timestamp(start),
+            util::format_timestamp(end)
+        );
+    } else if csv {
+        print!("{}", report.to_csv(by));
+    } else {
+        view::print(config, report.to_markdown())?;
+    }
+    Ok(())
+}
+
+pub fn find<F>(
+    debug: F,
+    config: &Config,
+    store: &Store,
+    query: &[String],
+    number: usize,
+    pick: bool,
+) -> Result<(), Box<dyn Error>>
+where
+    F: Fn(&str),
+{
+    debug(&format!(
+        "find {:?} number: {:?} pick: {:?}",
+        query, number, pick
+    ));
+    let mut hits = store
+        .get()
+        .values()
+        .filter_map(|item| {
+            // every term has to match, the best matching field counts
+            query
+                .iter()
+                .map(|term| item.fuzzy_score(term))
+                .sum::<Option<i64>>()
+                .map(|score| (item, score))
+        })
+        .collect::<Vec<_>>();
+    // best score first, recently modified items win on equal scores
+    hits.sort_by(|(a, sa), (b, sb)| {
+        sb.cmp(sa)
+            .then(b.last_modified().cmp(&a.last_modified()))
+            .then(a.id().cmp(b.id()))
+    });
+    hits.truncate(number);
+    for (item, score) in &hits {
+        debug(&format!("find: {} score {}", item.id(), score));
+    }
+
+    if pick {
+        match hits.first() {
+            Some((item, _)) => println!("{}", item.id()),
+            None => return Err("Nothing found.".into()),
+        }
+        return Ok(());
+    }
+    let max_id_len = hits.iter().map(|(i, _)| i.id().len()).max().unwrap_or(0);
+    let text = hits
+        .iter()
+        .map(|(item, _)| {
+            format!(
+                "‎ {}\n",
+                item.print(
+                    max_id_len,
+                    !item.children().is_empty(),
+                    store.rollup(item.id()).as_ref(),
+                    None
+                )
+            )
+        })
+        .collect::<String>();
+    view::print(config, text)?;
+    Ok(())
+}
+
+pub fn merge<F>(
+    debug: F,
+    config: &Config,
+    store: &mut Store,
+    keep: &str,
+    drop: &str,
+    yes: bool,
+) -> Result<(), Box<dyn Error>>
+where
+    F: Fn(&str),
+{
+    debug(&format!("merge {:?} into {:?}", drop, keep));
+    // drop is removed like with rm
+    confirm(config, store, "Remove", &[drop.to_string()], yes)?;
+    store.merge(keep, drop)?;
+    done(config, &format!("{} merged into {}.", drop, keep));
+    view::print(
+        config,
+        store
+            .get_item(keep)
+            .unwrap()
+            .print_long(false, store.rollup(keep).as_ref()),
+    )?;
+    Ok(())
+}
+
+pub fn split<F>(
+    debug: F,
+    config: &Config,
+    store: &mut Store,
+    id: &str,
+) -> Result<(), Box<dyn Error>>
+where
+    F: Fn(&str),
+{
+    debug(&format!("split {:?}", id));
+    let parent = store.get_item(id).ok_or("Could not found id")?.clone();
+    let (intro, sections) = util::split_sections(parent.content());
+    if sections.is_empty() {
+        return Err("There are no headings or bullets to split.".into());
+    }
+    for section in sections {
+        let mut _id = util::generate_id();
+        while store.get_item(&_id).is_some() {
+            _id = util::generate_id();
+        }
+        debug(&format!("split: new child {:?}", _id));
+        let mut item = Item::new(
+            _id,
+            vec![],
+            vec![parent.id().to_owned()],
+            parent.tags().to_owned(),
+        );
+        item.set_content(section);
+        store.add(item)?;
+    }
+    store.get_item_mut(id).unwrap().set_content(intro);
+    view::print(
+        config,
+        store
+            .get_item(id)
+            .unwrap()
+            .print_long(false, store.rollup(id).as_ref()),
+    )?;
+    Ok(())
+}
+
 pub fn show<F>(debug: F, config: &Config, path: &str) -> Result<(), Box<dyn Error>>
 where
     F: Fn(&str),
@@ -325,6 +994,68 @@ where
     Ok(())
 }
 
+pub fn query_list<F>(debug: F, config: &Config) -> Result<(), Box<dyn Error>>
+where
+    F: Fn(&str),
+{
+    debug(&format!("query list {:?}", config.queries));
+    if config.queries.is_empty() {
+        println!("No saved queries, see cake query save --help");
+    }
+    for (name, query) in &config.queries {
+        println!("{} {}", format!("@{}", name).yellow(), query);
+    }
+    Ok(())
+}
+
+pub fn query_save<F>(
+    debug: F,
+    config: &mut Config,
+    store: &Store,
+    name: &str,
+    selector: &[String],
+) -> Result<(), Box<dyn Error>>
+where
+    F: Fn(&str),
+{
+    let name = name.strip_prefix('@').unwrap_or(name);
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return Err(format!("Invalid query name '{}'", name).into());
+    }
+    // a single argument is the whole selector, e.g. "-t work --started"
+    let query = if selector.len() == 1 {
+        selector[0].to_string()
+    } else {
+        util::join_args(selector)
+    };
+    debug(&format!("query save {:?}: {:?}", name, query));
+    // check the selector before saving it
+    let args = SelectorArgs::try_parse_from(&util::split_args(&query)?)?;
+    Selector::new(&args, store)?;
+    config.save_query(name, query);
+    if config.dry_run {
+        println!("Would save @{}.", name);
+    } else {
+        println!("Saved @{}.", name);
+    }
+    Ok(())
+}
+
+pub fn query_delete<F>(debug: F, config: &mut Config, name: &str) -> Result<(), Box<dyn Error>>
+where
+    F: Fn(&str),
+{
+    let name = name.strip_prefix('@').unwrap_or(name);
+    debug(&format!("query delete {:?}", name));
+    config.delete_query(name)?;
+    if config.dry_run {
+        println!("Would delete @{}.", name);
+    } else {
+        println!("Deleted @{}.", name);
+    }
+    Ok(())
+}
+
 pub fn config<F>(debug: F, config: &Config) -> Result<(), Box<dyn Error>>
 where
     F: Fn(&str),
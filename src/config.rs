@@ -1,6 +1,7 @@
 use crate::util;
 use crate::Error;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::env::current_dir;
 use std::fs;
 use termimad::MadSkin;
@@ -10,7 +11,6 @@ use termimad::MadSkin;
 // default saving location -> md or json
 // aliases for run cmd or hook cmds?
 // default selectors
-// default modifiers for one or multiple hits
 // hire_recursive_elements
 // default editor or environment var?
 // .cake folder in ~ with default md/json file?
@@ -20,6 +20,10 @@ pub struct Config {
     /// default initialization to false
     #[serde(skip)]
     dirty: bool,
+    /// set by --dry-run, changing commands leave out their result messages,
+    /// the diff of the items shows what would change
+    #[serde(skip)]
+    pub dry_run: bool,
     /// editor for cake add
     pub editor: String,
     /// disable all git actions
@@ -40,6 +44,23 @@ pub struct Config {
     /// -1 to never go into scrollview
     /// 127 as maximum
     pub scrollview_threshold: i8,
+    /// When a single-target command like start selects more items than the threshold,
+    /// choose one of them in a picker, only if the output is a terminal.
+    /// -1 to never pick and use all items
+    #[serde(default = "default_picker_threshold")]
+    pub picker_threshold: i8,
+    /// Ask before destructive commands like rm, tag, tags and merge change more items
+    /// than the threshold, --yes skips the question. -1 to never ask
+    #[serde(default = "default_confirm_threshold")]
+    pub confirm_threshold: i8,
+    /// Only one item can run at a time, start stops every other running item first.
+    /// Same as start --switch
+    #[serde(default)]
+    pub single_timer: bool,
+    /// named selector arguments, used as @name in every command which selects items
+    /// e.g. today: "-t work --started"
+    #[serde(default)]
+    pub queries: BTreeMap<String, String>,
 }
 
 const DEFAULT_SKIN_GRUVBOX: &str = r###"# This Hjson file is the default gruvbox skin.
@@ -63,6 +84,14 @@ horizontal-rule: "~ #00cafe"
 table: "#540 left"
 scrollbar: "red yellow""###;
 
+fn default_picker_threshold() -> i8 {
+    1
+}
+
+fn default_confirm_threshold() -> i8 {
+    1
+}
+
 impl Config {
     pub fn new() -> Result<Config, Box<dyn Error>> {
         if let Ok(serialized) = std::fs::read_to_string(util::config_file()?) {
@@ -71,6 +100,7 @@ impl Config {
             // default settings on linux at ~/.config/cake
             Ok(Config {
                 dirty: true,
+                dry_run: false,
                 editor: std::env::var("EDITOR").unwrap_or("vim".to_string()),
                 disable_git: false,
                 git_branch_name: "cake".to_string(),
@@ -80,6 +110,10 @@ impl Config {
                 default_file_path: util::default_save_file("cake.md")?,
                 skin_file_name: "gruvbox.hjson".to_string(),
                 scrollview_threshold: 30,
+                picker_threshold: default_picker_threshold(),
+                confirm_threshold: default_confirm_threshold(),
+                single_timer: false,
+                queries: BTreeMap::new(),
             })
         }
     }
@@ -92,6 +126,34 @@ impl Config {
         Ok(())
     }
 
+    /// replaces every argument @name of a saved query by its selector arguments
+    pub fn expand_queries(&self, args: Vec<String>) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut res = vec![];
+        for arg in args {
+            match arg
+                .strip_prefix('@')
+                .and_then(|name| self.queries.get(name))
+            {
+                Some(query) => res.append(&mut util::split_args(query)?),
+                None => res.push(arg),
+            }
+        }
+        Ok(res)
+    }
+
+    pub fn save_query(&mut self, name: &str, query: String) {
+        self.queries.insert(name.to_string(), query);
+        self.dirty = true;
+    }
+
+    pub fn delete_query(&mut self, name: &str) -> Result<(), Box<dyn Error>> {
+        if self.queries.remove(name).is_none() {
+            return Err(format!("Could not found query '{}'", name).into());
+        }
+        self.dirty = true;
+        Ok(())
+    }
+
     pub fn find_save_file(&self) -> Result<String, Box<dyn Error>> {
         util::find_save_file(&mut current_dir()?, &self.save_file_name)
     }
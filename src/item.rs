@@ -1,7 +1,9 @@
 use crate::error;
 use crate::util;
 use core::fmt;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::str::FromStr;
 use termimad::crossterm::style::Stylize;
 
@@ -16,6 +18,189 @@ const PREFIX_TAGS: &str = "| tags | ";
 const PREFIX_TIMETRACK: &str = "| timetrack | ";
 const PREFIX_PARENTS: &str = "| parents | ";
 const PREFIX_CHILDREN: &str = "| children | ";
+const PREFIX_SUBTREE: &str = "| subtree | ";
+/// separates the time entries in the timetrack row, e.g.
+/// start - end # note; start - end; start
+const TIME_ENTRY_DELIMITER: &str = "; ";
+const TIME_RANGE_DELIMITER: &str = " - ";
+const TIME_NOTE_PREFIX: &str = " # ";
+
+/// items with this tag count as done in the subtree summary
+const DONE_TAG: &str = "done";
+/// separates the namespaces of a tag, e.g. client-a/backend
+pub const TAG_NAMESPACE_DELIMITER: char = '/';
+
+/// order of listed items, see Sort
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortKey {
+    #[default]
+    Created,
+    Modified,
+    Id,
+    Spent,
+    Children,
+    Title,
+}
+
+/// compares items by the key, equal items are ordered by id
+/// to keep the output the same between runs
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Sort {
+    pub key: SortKey,
+    pub reverse: bool,
+}
+
+impl Sort {
+    pub fn cmp(&self, a: &Item, b: &Item) -> Ordering {
+        self.cmp_key(a, b).then(a.id().cmp(b.id()))
+    }
+
+    /// compares only by the key, a stable sort keeps the order of equal items
+    pub fn cmp_key(&self, a: &Item, b: &Item) -> Ordering {
+        let ord = match self.key {
+            SortKey::Created => a.timestamp().cmp(&b.timestamp()),
+            SortKey::Modified => a.last_modified().cmp(&b.last_modified()),
+            SortKey::Id => a.id().cmp(b.id()),
+            SortKey::Spent => a.spent().cmp(&b.spent()),
+            SortKey::Children => a.children().len().cmp(&b.children().len()),
+            SortKey::Title => a.title().to_lowercase().cmp(&b.title().to_lowercase()),
+        };
+        if self.reverse {
+            ord.reverse()
+        } else {
+            ord
+        }
+    }
+}
+
+/// one tracked session, the end is missing while it is running
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TimeEntry {
+    pub start: i64,
+    pub end: Option<i64>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub note: String,
+}
+
+impl TimeEntry {
+    /// converts the old format of alternating starts and stops
+    fn from_flat(timetrack: &[i64]) -> Vec<TimeEntry> {
+        timetrack
+            .chunks(2)
+            .map(|c| TimeEntry {
+                start: c[0],
+                end: c.get(1).copied(),
+                note: "".to_string(),
+            })
+            .collect()
+    }
+
+    /// tracked seconds, a running entry counts up to now
+    pub fn duration(&self) -> i64 {
+        self.end.unwrap_or_else(util::timestamp) - self.start
+    }
+
+    /// removes characters which are used by the markdown representation
+    pub fn set_note(&mut self, note: &str) {
+        self.note = note
+            .replace(['|', ';', '\n'], " ")
+            .replace(TIME_NOTE_PREFIX.trim(), "")
+            .trim()
+            .to_string();
+    }
+}
+
+impl fmt::Display for TimeEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", util::format_timestamp(self.start))?;
+        if let Some(end) = self.end {
+            write!(f, "{}{}", TIME_RANGE_DELIMITER, util::format_timestamp(end))?;
+        }
+        if !self.note.is_empty() {
+            write!(f, "{}{}", TIME_NOTE_PREFIX, self.note)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for TimeEntry {
+    type Err = error::ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (times, note) = s.split_once(TIME_NOTE_PREFIX).unwrap_or((s, ""));
+        let (start, end) = match times.split_once(TIME_RANGE_DELIMITER) {
+            Some((start, end)) => (start, Some(util::parse_timestamp(end)?)),
+            None => (times, None),
+        };
+        Ok(TimeEntry {
+            start: util::parse_timestamp(start)?,
+            end,
+            note: note.to_string(),
+        })
+    }
+}
+
+/// parses the timetrack row of the markdown representation,
+/// the old format of alternating starts and stops is migrated
+fn parse_timetrack(s: &str) -> Result<Vec<TimeEntry>, error::ParseError> {
+    if s.is_empty() {
+        return Ok(vec![]);
+    }
+    if !s.contains(TIME_RANGE_DELIMITER) && !s.contains(TIME_NOTE_PREFIX) {
+        let flat = s
+            .split(", ")
+            .map(util::parse_timestamp)
+            .collect::<Result<Vec<i64>, error::ParseError>>()?;
+        return Ok(TimeEntry::from_flat(&flat));
+    }
+    s.split(TIME_ENTRY_DELIMITER)
+        .map(TimeEntry::from_str)
+        .collect()
+}
+
+/// reads the timetrack of the json representation,
+/// the old format of alternating starts and stops is migrated
+fn deserialize_timetrack<'de, D>(deserializer: D) -> Result<Vec<TimeEntry>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Timetrack {
+        Flat(Vec<i64>),
+        Entries(Vec<TimeEntry>),
+    }
+    Ok(match Timetrack::deserialize(deserializer)? {
+        Timetrack::Flat(flat) => TimeEntry::from_flat(&flat),
+        Timetrack::Entries(entries) => entries,
+    })
+}
+
+/// summary over all descendants of an item, shared descendants are counted once
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Rollup {
+    /// tracked seconds of the item and all descendants
+    pub spent: i64,
+    pub descendants: usize,
+    pub open: usize,
+    pub started: usize,
+    pub done: usize,
+}
+
+impl fmt::Display for Rollup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.spent > 0 {
+            write!(f, "{}, ", util::timestamp_to_hms(self.spent))?;
+        }
+        write!(
+            f,
+            "{} open, {} started, {}% done",
+            self.open,
+            self.started,
+            (self.done * 100).checked_div(self.descendants).unwrap_or(0)
+        )
+    }
+}
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Item {
@@ -23,7 +208,8 @@ pub struct Item {
     children: Vec<String>,
     parents: Vec<String>,
     tags: Vec<String>,
-    timetrack: Vec<i64>,
+    #[serde(deserialize_with = "deserialize_timetrack")]
+    timetrack: Vec<TimeEntry>,
     content: String,
     timestamp: i64,     // creation timestamp
     last_modified: i64, // last update timestamp
@@ -31,7 +217,7 @@ pub struct Item {
 
 impl fmt::Display for Item {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "{}", self.print_long(false))
+        writeln!(f, "{}", self.print_long(false, None))
     }
 }
 
@@ -73,21 +259,9 @@ impl FromStr for Item {
         } else {
             return Err(err);
         };
-        let timetrack: Vec<i64>;
+        let timetrack: Vec<TimeEntry>;
         if let Some(raw_timetrack) = lines.next() {
-            let pollished = util::extract_metadata(raw_timetrack, PREFIX_TIMETRACK)?;
-            if pollished.is_empty() {
-                timetrack = vec![];
-            } else {
-                // Result implements FromIterator, so you can move the Result outside and iterators
-                // will take care of the rest (including stopping iteration if an error is found).
-                // https://stackoverflow.com/questions/26368288/how-do-i-stop-iteration-and-return-an-error-when-iteratormap-returns-a-result
-                // super cool :D
-                timetrack = pollished
-                    .split(", ")
-                    .map(|a| util::parse_timestamp(a))
-                    .collect::<Result<Vec<i64>, error::ParseError>>()?;
-            }
+            timetrack = parse_timetrack(&util::extract_metadata(raw_timetrack, PREFIX_TIMETRACK)?)?;
         } else {
             return Err(err);
         };
@@ -140,11 +314,19 @@ impl Item {
      * short info about the item
      * prints only first line of the content and id
      * the id is surrounded by spaces to reach spacer_len
+     * the rollup of the subtree is appended if given
+     * matches of highlight are marked in the content
      */
-    pub fn print(&self, spacer_len: usize, has_children: bool) -> String {
+    pub fn print(
+        &self,
+        spacer_len: usize,
+        has_children: bool,
+        rollup: Option<&Rollup>,
+        highlight: Option<&Regex>,
+    ) -> String {
         let border = if has_children { "\\" } else { "|" };
         return format!(
-            "{}{}{}{} {}",
+            "{}{}{}{} {}{}",
             border,
             util::space(&self.id, spacer_len),
             if self.is_started() {
@@ -153,46 +335,48 @@ impl Item {
                 "".white()
             },
             border,
-            self.content.split("\n").next().unwrap()
+            util::highlight(self.content.split("\n").next().unwrap(), highlight),
+            match rollup {
+                Some(r) => format!(" {}", format!("[{}]", r).grey()),
+                None => "".to_string(),
+            }
         );
     }
 
     /// # Arguments
-    /// * `serialize` - if the serialize flag is true, the timetrack is printed as date,
-    /// else only the timedifferences are printed
+    /// * `serialize` - if the serialize flag is true, the timetrack is printed as dates,
+    ///   else only the durations of completed entries with their notes are printed
+    /// * `rollup` - summary of the subtree, shown as additional row if not serialized
     ///
     /// # Returns
     /// long info about the item
     /// first: a table of metadata
     /// second: content
-    pub fn print_long(&self, serialize: bool) -> String {
-        let tt;
-        if serialize {
-            tt = self
-                .timetrack
+    pub fn print_long(&self, serialize: bool, rollup: Option<&Rollup>) -> String {
+        let tt = if serialize {
+            self.timetrack
                 .iter()
-                .map(|t| util::format_timestamp(*t))
-                .collect::<Vec<String>>();
+                .map(|e| e.to_string())
+                .collect::<Vec<String>>()
+                .join(TIME_ENTRY_DELIMITER)
         } else {
-            // calculate timedifferences
-            let even = self
-                .timetrack
-                .iter()
-                .enumerate()
-                .filter(|(i, _)| i % 2 == 0);
-            let odd = self
+            let durations = self
                 .timetrack
                 .iter()
-                .enumerate()
-                .filter(|(i, _)| i % 2 == 1);
-            tt = even
-                .zip(odd)
-                .map(|((_, first), (_, second))| second - first)
-                .map(|t| util::timestamp_to_hms(t))
+                .filter(|e| e.end.is_some())
+                .map(|e| match e.note.as_str() {
+                    "" => util::timestamp_to_hms(e.duration()),
+                    note => format!("{} ({})", util::timestamp_to_hms(e.duration()), note),
+                })
                 .collect::<Vec<String>>();
-        }
+            util::vec_to_str(&durations)
+        };
+        let subtree = match rollup {
+            Some(r) if !serialize => format!("{}{}|\n", PREFIX_SUBTREE, r),
+            _ => "".to_string(),
+        };
         let res = format!(
-            "{}{}|\n{}\n{}{}|\n{}{}|\n{}{}|\n{}{}|\n{}{}|\n{}{}|\n\n{}",
+            "{}{}|\n{}\n{}{}|\n{}{}|\n{}{}|\n{}{}|\n{}{}|\n{}{}|\n{}\n{}",
             PREFIX_ID,
             self.id,
             TABLE_HEADER_DELIMITER,
@@ -201,13 +385,18 @@ impl Item {
             PREFIX_LAST_MODIFIED,
             util::format_timestamp(self.last_modified),
             PREFIX_TAGS,
-            util::vec_to_str(&self.tags),
+            if serialize {
+                util::vec_to_str(&self.tags)
+            } else {
+                util::vec_to_str(&util::group_tags(&self.tags, TAG_NAMESPACE_DELIMITER))
+            },
             PREFIX_TIMETRACK,
-            util::vec_to_str(&tt),
+            tt,
             PREFIX_PARENTS,
             util::vec_to_str(&self.parents),
             PREFIX_CHILDREN,
             util::vec_to_str(&self.children),
+            subtree,
             self.content
         );
         res
@@ -251,6 +440,16 @@ impl Item {
         &self.tags
     }
 
+    /// checks if the item has the tag or a tag in the namespace of it,
+    /// e.g. client-a matches client-a/backend
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| {
+            t == tag
+                || t.strip_prefix(tag)
+                    .is_some_and(|rest| rest.starts_with(TAG_NAMESPACE_DELIMITER))
+        })
+    }
+
     pub fn append_tags(&mut self, tags: Vec<String>) -> &mut Self {
         for tag in tags {
             if !self.tags.contains(&tag) {
@@ -271,6 +470,96 @@ impl Item {
         self.update_last_modified();
     }
 
+    /// renames the tag and the tags in its namespace, removes them if new is None.
+    /// Returns true if a tag was changed
+    pub fn rename_tag(&mut self, old: &str, new: Option<&str>) -> bool {
+        if !self.has_tag(old) {
+            return false;
+        }
+        let mut tags: Vec<String> = vec![];
+        for tag in &self.tags {
+            let renamed = match tag.strip_prefix(old) {
+                Some(rest) if rest.is_empty() || rest.starts_with(TAG_NAMESPACE_DELIMITER) => {
+                    new.map(|n| format!("{}{}", n, rest))
+                }
+                _ => Some(tag.to_owned()),
+            };
+            if let Some(t) = renamed {
+                if !tags.contains(&t) {
+                    tags.push(t);
+                }
+            }
+        }
+        self.tags = tags;
+        self.update_last_modified();
+        true
+    }
+
+    /// describes the changed fields from self to the new version,
+    /// the modification time is left out
+    pub fn diff(&self, new: &Item) -> Vec<String> {
+        let mut res = vec![];
+        for (name, old_value, new_value) in [
+            ("tags", &self.tags, &new.tags),
+            ("parents", &self.parents, &new.parents),
+            ("children", &self.children, &new.children),
+        ] {
+            if old_value != new_value {
+                res.push(format!(
+                    "{}: {} → {}",
+                    name,
+                    util::vec_to_str(old_value),
+                    util::vec_to_str(new_value)
+                ));
+            }
+        }
+        if self.timetrack != new.timetrack {
+            let format = |tt: &Vec<TimeEntry>| {
+                tt.iter()
+                    .map(|e| e.to_string())
+                    .collect::<Vec<String>>()
+                    .join(TIME_ENTRY_DELIMITER)
+            };
+            res.push(format!(
+                "timetrack: {} → {}",
+                format(&self.timetrack),
+                format(&new.timetrack)
+            ));
+        }
+        if self.content != new.content {
+            res.push("content:".to_string());
+            res.append(&mut util::diff_lines(&self.content, &new.content));
+        }
+        res
+    }
+
+    /// how well the term matches the id, the tags or the content, the best field counts.
+    /// An exact id wins, ids and tags count more than the content
+    pub fn fuzzy_score(&self, term: &str) -> Option<i64> {
+        let id = if self.id == term {
+            Some(100)
+        } else {
+            util::fuzzy_score(term, &self.id).map(|s| s * 2)
+        };
+        let tags = self
+            .tags
+            .iter()
+            .filter_map(|t| util::fuzzy_score(term, t).map(|s| s * 2))
+            .max();
+        let content = util::fuzzy_score(term, &self.content);
+        [id, tags, content].into_iter().flatten().max()
+    }
+
+    /// first line of the content without heading markers
+    pub fn title(&self) -> &str {
+        self.content
+            .lines()
+            .next()
+            .unwrap_or("")
+            .trim_start_matches('#')
+            .trim()
+    }
+
     pub fn content(&self) -> &String {
         return &self.content;
     }
@@ -284,31 +573,80 @@ impl Item {
         return self.timestamp;
     }
 
+    pub fn last_modified(&self) -> i64 {
+        self.last_modified
+    }
+
     pub fn is_started(&self) -> bool {
-        return self.timetrack.len() % 2 == 1;
+        self.timetrack.last().is_some_and(|e| e.end.is_none())
     }
 
     pub fn is_stopped(&self) -> bool {
-        return self.timetrack.len() % 2 == 0;
+        !self.is_started()
+    }
+
+    pub fn time_entries(&self) -> &Vec<TimeEntry> {
+        &self.timetrack
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.tags.iter().any(|t| t == DONE_TAG)
+    }
+
+    /// start and end of every timetrack interval, a running interval ends now
+    pub fn intervals(&self) -> Vec<(i64, i64)> {
+        self.timetrack
+            .iter()
+            .map(|e| (e.start, e.end.unwrap_or_else(util::timestamp)))
+            .collect()
+    }
+
+    /// tracked time in seconds, a running interval counts up to now
+    pub fn spent(&self) -> i64 {
+        self.intervals()
+            .iter()
+            .map(|(start, end)| end - start)
+            .sum()
     }
 
-    pub fn start(&mut self) -> Result<(), String> {
+    /// tracked time in seconds inside the given time span
+    pub fn spent_between(&self, start: i64, end: i64) -> i64 {
+        self.intervals()
+            .iter()
+            .map(|(s, e)| (*e.min(&end) - *s.max(&start)).max(0))
+            .sum()
+    }
+
+    /// checks if any timetrack interval overlaps the given time span
+    pub fn worked_between(&self, start: i64, end: i64) -> bool {
+        self.intervals()
+            .iter()
+            .any(|(s, e)| *s <= end && *e >= start)
+    }
+
+    /// starts the timetrack now or at the given time after the last entry
+    pub fn start(&mut self, at: Option<i64>) -> Result<(), String> {
         if self.is_stopped() {
-            self.timetrack.push(util::timestamp());
+            self.timetrack.push(TimeEntry {
+                start: self.check_entry(at)?,
+                ..Default::default()
+            });
             self.update_last_modified();
             Ok(())
         } else {
             Err(format!(
                 "{} runs already since {}",
                 self.id.to_owned(),
-                util::format_timestamp(*self.timetrack.last().unwrap())
+                util::format_timestamp(self.timetrack.last().unwrap().start)
             ))
         }
     }
 
-    pub fn stop(&mut self) -> Result<(), String> {
+    /// stops the timetrack now or at the given time after the start
+    pub fn stop(&mut self, at: Option<i64>) -> Result<(), String> {
         if self.is_started() {
-            self.timetrack.push(util::timestamp());
+            let end = self.check_entry(at)?;
+            self.timetrack.last_mut().unwrap().end = Some(end);
             self.update_last_modified();
             Ok(())
         } else {
@@ -316,6 +654,114 @@ impl Item {
         }
     }
 
+    /// time of a new timetrack entry, it has to be after the last entry and not in the future
+    fn check_entry(&self, at: Option<i64>) -> Result<i64, String> {
+        let now = util::timestamp();
+        let t = at.unwrap_or(now);
+        if t > now {
+            return Err(format!("{} is in the future.", util::format_timestamp(t)));
+        }
+        match self.timetrack.last().map(|e| e.end.unwrap_or(e.start)) {
+            Some(last) if t < last => Err(format!(
+                "{} is before the last entry of {} at {}.",
+                util::format_timestamp(t),
+                self.id,
+                util::format_timestamp(last)
+            )),
+            _ => Ok(t),
+        }
+    }
+
+    /// inserts a completed interval, it must not overlap tracked time
+    pub fn track(&mut self, start: i64, end: i64) -> Result<(), String> {
+        self.insert_time_entry(TimeEntry {
+            start,
+            end: Some(end),
+            ..Default::default()
+        })?;
+        self.update_last_modified();
+        Ok(())
+    }
+
+    /// changes start and end of the entry at index, the entry is validated like cake track
+    pub fn edit_time_entry(
+        &mut self,
+        index: usize,
+        start: Option<i64>,
+        end: Option<i64>,
+    ) -> Result<(), String> {
+        self.check_time_entry_index(index)?;
+        let old = self.timetrack.remove(index);
+        let entry = TimeEntry {
+            start: start.unwrap_or(old.start),
+            end: end.or(old.end),
+            note: old.note.to_owned(),
+        };
+        if let Err(e) = self.insert_time_entry(entry) {
+            self.timetrack.insert(index, old);
+            return Err(e);
+        }
+        self.update_last_modified();
+        Ok(())
+    }
+
+    pub fn annotate_time_entry(&mut self, index: usize, note: &str) -> Result<(), String> {
+        self.check_time_entry_index(index)?;
+        self.timetrack[index].set_note(note);
+        self.update_last_modified();
+        Ok(())
+    }
+
+    pub fn delete_time_entry(&mut self, index: usize) -> Result<TimeEntry, String> {
+        self.check_time_entry_index(index)?;
+        self.update_last_modified();
+        Ok(self.timetrack.remove(index))
+    }
+
+    fn check_time_entry_index(&self, index: usize) -> Result<(), String> {
+        if index >= self.timetrack.len() {
+            return Err(format!(
+                "{} has no time entry {}, it has {}.",
+                self.id,
+                index + 1,
+                self.timetrack.len()
+            ));
+        }
+        Ok(())
+    }
+
+    /// inserts the entry ordered by start, it must not be in the future
+    /// or overlap other entries. A running entry lasts until now
+    fn insert_time_entry(&mut self, entry: TimeEntry) -> Result<(), String> {
+        let now = util::timestamp();
+        let end = entry.end.unwrap_or(now);
+        if entry.end.is_some() && end <= entry.start {
+            return Err("The interval has to end after its start.".to_string());
+        }
+        if end > now || entry.start > now {
+            return Err(format!("{} is in the future.", util::format_timestamp(end)));
+        }
+        if let Some((s, e)) = self
+            .intervals()
+            .into_iter()
+            .find(|(s, e)| entry.start < *e && end > *s || entry.start == *s)
+        {
+            return Err(format!(
+                "The interval overlaps the tracked time of {} from {} to {}.",
+                self.id,
+                util::format_timestamp(s),
+                util::format_timestamp(e)
+            ));
+        }
+        let pos = self
+            .timetrack
+            .iter()
+            .position(|e| e.start > entry.start)
+            .unwrap_or(self.timetrack.len());
+        self.timetrack.insert(pos, entry);
+        Ok(())
+    }
+
     pub fn set(&mut self, item: Item) {
         *self = item;
         self.update_last_modified();
@@ -328,4 +774,63 @@ impl Item {
         self.parents.append(&mut item.parents);
         self.update_last_modified();
     }
+
+    /// takes over content, tags and timetrack of a duplicate,
+    /// relations are handled by the store
+    pub fn absorb(&mut self, other: &Item) -> Result<(), String> {
+        self.timetrack = merge_timetracks(&self.timetrack, &other.timetrack)?;
+        if self.content.trim().is_empty() {
+            self.content = other.content.to_string();
+        } else if !other.content.trim().is_empty() {
+            self.content = format!("{}\n{}", self.content.trim_end(), other.content);
+        }
+        self.append_tags(other.tags.to_owned());
+        self.timestamp = self.timestamp.min(other.timestamp);
+        self.update_last_modified();
+        Ok(())
+    }
+}
+
+/// unites two timetracks into one ordered timetrack,
+/// overlapping entries are joined so no time is counted twice
+fn merge_timetracks(a: &[TimeEntry], b: &[TimeEntry]) -> Result<Vec<TimeEntry>, String> {
+    let running = |tt: &[TimeEntry]| tt.last().is_some_and(|e| e.end.is_none());
+    if running(a) && running(b) {
+        return Err("Both items are running, stop one of them first.".to_string());
+    }
+    let mut entries = a
+        .iter()
+        .chain(b.iter())
+        .cloned()
+        .collect::<Vec<TimeEntry>>();
+    entries.sort_by_key(|e| e.start);
+    let mut merged: Vec<TimeEntry> = vec![];
+    for entry in entries {
+        // a running entry lasts until the end of time
+        let end = entry.end.unwrap_or(i64::MAX);
+        if end < entry.start {
+            return Err(format!(
+                "Invalid interval from {} to {}",
+                util::format_timestamp(entry.start),
+                util::format_timestamp(end)
+            ));
+        }
+        match merged.last_mut() {
+            Some(last) if entry.start <= last.end.unwrap_or(i64::MAX) => {
+                last.end = match (last.end, entry.end) {
+                    (Some(a), Some(b)) => Some(a.max(b)),
+                    _ => None,
+                };
+                if !entry.note.is_empty() && entry.note != last.note {
+                    let note = match last.note.as_str() {
+                        "" => entry.note,
+                        note => format!("{}, {}", note, entry.note),
+                    };
+                    last.set_note(&note);
+                }
+            }
+            _ => merged.push(entry),
+        }
+    }
+    Ok(merged)
 }
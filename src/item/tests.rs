@@ -1,4 +1,6 @@
 use crate::item::*;
+use crate::util::*;
+use chrono::{Local, TimeZone};
 
 #[test]
 fn test_str_to_vec() {
@@ -13,26 +15,36 @@ fn test_vec_to_str() {
 
 #[test]
 fn test_ft() {
-    assert_eq!(ft(1678197184), "Tue Mar  7 14:53:04 2023 +0100");
+    // the output depends on the local time zone, it has to be parsed back
+    let formatted = format_timestamp(1678197184);
+    let local = Local.timestamp_opt(1678197184, 0).unwrap();
+    assert!(formatted.contains(&local.format("%b %e").to_string()));
+    assert!(formatted.contains("2023"));
+    assert_eq!(parse_timestamp(&formatted).unwrap(), 1678197184);
 }
 
 #[test]
 fn test_tf() {
-    assert_eq!(tf("Tue Mar  7 14:53:04 2023 +0100").unwrap(), 1678197184);
+    assert_eq!(
+        parse_timestamp("Tue Mar  7 14:53:04 2023 +0100").unwrap(),
+        1678197184
+    );
 }
 
 #[test]
 fn test_hm() {
-    assert_eq!(hms(1000), "16m40s");
+    assert_eq!(timestamp_to_hms(1000), "16m40s");
+    assert_eq!(timestamp_to_hms(3700), "1h1m40s");
 }
 
 #[test]
 fn test_from_string() {
     let serialized = r#"| id | easycase|
+|---|---|
 | timestamp | Tue Mar  7 13:53:04 2023 +0100|
 | last modified | Tue Oct 17 17:13:12 2023 +0200|
 | tags | done, nice|
-| timetrack | Tue Mar  7 13:55:06 2023 +0100, Tue Mar  7 13:55:20 2023 +0100, Tue Mar  7 13:56:42 2023 +0100, Tue Mar  7 13:56:47 2023 +0100, Mon Oct 16 21:51:53 2023 +0200, Mon Oct 16 21:52:00 2023 +0200, Tue Oct 17 09:23:05 2023 +0200, Tue Oct 17 09:29:44 2023 +0200, Tue Oct 17 17:03:37 2023 +0200, Tue Oct 17 17:13:12 2023 +0200|
+| timetrack | Tue Mar  7 13:55:06 2023 +0100 - Tue Mar  7 13:55:20 2023 +0100; Tue Mar  7 13:56:42 2023 +0100 - Tue Mar  7 13:56:47 2023 +0100 # setup, first try; Mon Oct 16 21:51:53 2023 +0200 - Mon Oct 16 21:52:00 2023 +0200; Tue Oct 17 09:23:05 2023 +0200 - Tue Oct 17 09:29:44 2023 +0200; Tue Oct 17 17:03:37 2023 +0200|
 | parents | frech|
 | children | a76, 2c5|
 
@@ -49,5 +61,170 @@ fn burger() {
 [link](https://hayrave.de)
 "#;
     let itm = Item::from_str(serialized).unwrap();
-    assert_eq!(itm.print_long(true), serialized);
+    // timestamps are written in the local time zone
+    let re = regex::Regex::new(r"\w{3} \w{3} [ \d]\d \d{2}:\d{2}:\d{2} \d{4} [+-]\d{4}").unwrap();
+    let expected = re.replace_all(serialized, |caps: &regex::Captures| {
+        format_timestamp(parse_timestamp(&caps[0]).unwrap())
+    });
+    assert_eq!(itm.print_long(true, None), expected);
+}
+
+#[test]
+fn test_parse_timetrack() {
+    // old format of alternating starts and stops
+    let old = "Tue Mar  7 13:55:06 2023 +0100, Tue Mar  7 13:55:20 2023 +0100, \
+        Tue Mar  7 13:56:42 2023 +0100";
+    let tt = parse_timetrack(old).unwrap();
+    assert_eq!(
+        tt,
+        TimeEntry::from_flat(&[1678193706, 1678193720, 1678193802])
+    );
+    assert_eq!(tt[0].end, Some(1678193720));
+    assert_eq!(tt[1].end, None);
+    let new = "Tue Mar  7 13:55:06 2023 +0100 - Tue Mar  7 13:55:20 2023 +0100 # a - b; \
+        Tue Mar  7 13:56:42 2023 +0100";
+    let tt = parse_timetrack(new).unwrap();
+    assert_eq!(tt[0].note, "a - b");
+    assert_eq!(tt[1].end, None);
+    assert!(parse_timetrack("yesterday").is_err());
+
+    let json: Vec<Item> = serde_json::from_str(
+        r#"[{"id":"a","children":[],"parents":[],"tags":[],"timetrack":[1,2,3],
+        "content":"","timestamp":0,"last_modified":0}]"#,
+    )
+    .unwrap();
+    assert_eq!(json[0].timetrack, TimeEntry::from_flat(&[1, 2, 3]));
+}
+
+#[test]
+fn test_rollup_display() {
+    let r = Rollup {
+        spent: 3600,
+        descendants: 4,
+        open: 3,
+        started: 1,
+        done: 1,
+    };
+    assert_eq!(r.to_string(), "1h, 3 open, 1 started, 25% done");
+}
+
+#[test]
+fn test_merge_timetracks() {
+    let merge =
+        |a: &[i64], b: &[i64]| merge_timetracks(&TimeEntry::from_flat(a), &TimeEntry::from_flat(b));
+    assert_eq!(
+        merge(&[1, 5, 10, 12], &[3, 7, 20]).unwrap(),
+        TimeEntry::from_flat(&[1, 7, 10, 12, 20])
+    );
+    assert_eq!(merge(&[1, 2], &[]).unwrap(), TimeEntry::from_flat(&[1, 2]));
+    assert_eq!(
+        merge(&[5], &[1, 2, 6, 8]).unwrap(),
+        TimeEntry::from_flat(&[1, 2, 5])
+    );
+    assert!(merge(&[1], &[2]).is_err());
+    assert!(merge(&[5, 1], &[]).is_err());
+}
+
+#[test]
+fn test_sort() {
+    let mut a = Item::new("b".to_string(), vec![], vec![], vec![]);
+    a.set_content("# Zebra".to_string());
+    let mut b = Item::new("a".to_string(), vec!["x".to_string()], vec![], vec![]);
+    b.set_content("apple".to_string());
+    let sort = |key, reverse| Sort { key, reverse };
+    assert_eq!(sort(SortKey::Title, false).cmp(&a, &b), Ordering::Greater);
+    assert_eq!(sort(SortKey::Title, true).cmp(&a, &b), Ordering::Less);
+    assert_eq!(sort(SortKey::Children, false).cmp(&a, &b), Ordering::Less);
+    assert_eq!(sort(SortKey::Id, true).cmp(&a, &b), Ordering::Less);
+    // equal keys are ordered by id, also when reversed
+    assert_eq!(sort(SortKey::Spent, false).cmp(&a, &b), Ordering::Greater);
+    assert_eq!(sort(SortKey::Spent, true).cmp(&a, &b), Ordering::Greater);
+    assert_eq!(sort(SortKey::Spent, false).cmp_key(&a, &b), Ordering::Equal);
+    assert_eq!(sort(SortKey::Id, true).cmp_key(&a, &b), Ordering::Less);
+}
+
+#[test]
+fn test_spent_between() {
+    let mut itm = Item::new("a".to_string(), vec![], vec![], vec![]);
+    itm.timetrack = TimeEntry::from_flat(&[10, 20, 30, 40]);
+    assert_eq!(itm.spent(), 20);
+    assert_eq!(itm.spent_between(15, 35), 10);
+    assert_eq!(itm.spent_between(20, 30), 0);
+    assert_eq!(itm.spent_between(i64::MIN, i64::MAX), 20);
+}
+
+#[test]
+fn test_rename_tag() {
+    let tags = ["a", "a/b", "ab", "c"].map(String::from).to_vec();
+    let mut itm = Item::new("x".to_string(), vec![], vec![], tags);
+    assert!(itm.rename_tag("a", Some("c")));
+    assert_eq!(itm.tags(), &vec!["c", "c/b", "ab"]);
+    assert!(!itm.rename_tag("a", None));
+    assert!(itm.rename_tag("c", None));
+    assert_eq!(itm.tags(), &vec!["ab"]);
+}
+
+#[test]
+fn test_diff() {
+    let old = Item::new("a".to_string(), vec![], vec![], vec!["x".to_string()]);
+    let mut new = old.clone();
+    assert!(old.diff(&new).is_empty());
+    new.append_tags(vec!["y".to_string()]);
+    new.set_content("hello".to_string());
+    assert_eq!(
+        old.diff(&new),
+        vec!["tags: x → x, y", "content:", "+ hello"]
+    );
+}
+
+#[test]
+fn test_track() {
+    let mut itm = Item::new("a".to_string(), vec![], vec![], vec![]);
+    itm.timetrack = TimeEntry::from_flat(&[10, 20, 40, 50]);
+    itm.track(25, 35).unwrap();
+    itm.track(0, 5).unwrap();
+    assert_eq!(
+        itm.timetrack,
+        TimeEntry::from_flat(&[0, 5, 10, 20, 25, 35, 40, 50])
+    );
+    assert!(itm.track(15, 22).is_err());
+    assert!(itm.track(30, 30).is_err());
+    assert!(itm.track(60, timestamp() + 100).is_err());
+    assert!(itm.start(Some(45)).is_err());
+    itm.start(Some(60)).unwrap();
+    assert!(itm.track(55, 65).is_err());
+    assert!(itm.stop(Some(59)).is_err());
+    itm.stop(Some(70)).unwrap();
+}
+
+#[test]
+fn test_edit_time_entry() {
+    let mut itm = Item::new("a".to_string(), vec![], vec![], vec![]);
+    itm.timetrack = TimeEntry::from_flat(&[10, 20, 30, 40]);
+    itm.edit_time_entry(1, Some(25), None).unwrap();
+    assert_eq!(itm.timetrack, TimeEntry::from_flat(&[10, 20, 25, 40]));
+    // the order is kept when an entry moves before another one
+    itm.edit_time_entry(1, Some(0), Some(5)).unwrap();
+    assert_eq!(itm.timetrack, TimeEntry::from_flat(&[0, 5, 10, 20]));
+    assert!(itm.edit_time_entry(1, Some(3), None).is_err());
+    assert!(itm.edit_time_entry(1, None, Some(5)).is_err());
+    assert!(itm.edit_time_entry(2, Some(50), None).is_err());
+    assert_eq!(itm.timetrack, TimeEntry::from_flat(&[0, 5, 10, 20]));
+
+    itm.annotate_time_entry(0, "a|b;c").unwrap();
+    assert_eq!(itm.timetrack[0].note, "a b c");
+    assert_eq!(itm.delete_time_entry(0).unwrap().note, "a b c");
+    assert_eq!(itm.timetrack, TimeEntry::from_flat(&[10, 20]));
+}
+
+#[test]
+fn test_fuzzy_score() {
+    let mut itm = Item::new("aha".to_string(), vec![], vec![], vec!["work".to_string()]);
+    itm.set_content("# Cake".to_string());
+    assert!(itm.fuzzy_score("ah").is_some());
+    assert!(itm.fuzzy_score("wrk").is_some());
+    assert!(itm.fuzzy_score("cke").is_some());
+    assert!(itm.fuzzy_score("xyz").is_none());
+    assert_eq!(itm.fuzzy_score("aha"), Some(100));
+    assert!(itm.fuzzy_score("ah") > itm.fuzzy_score("ca"));
 }
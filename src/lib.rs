@@ -3,6 +3,8 @@ mod config;
 mod error;
 mod git;
 mod item;
+mod query;
+mod report;
 mod selector;
 mod store;
 mod util;
@@ -11,10 +13,68 @@ mod view;
 use crate::store::Store;
 use clap::{Parser, Subcommand};
 use config::Config;
-use selector::Selector;
+use report::ReportBy;
+use selector::{Selector, SelectorArgs};
 use std::{env::args, error::Error, process::exit};
 use termimad::crossterm::style::Stylize;
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split(' ').map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_cli() {
+        let mut config = deser_hjson::from_str::<Config>(
+            r#"{
+                editor: vim
+                disable_git: true
+                git_branch_name: cake
+                git_remote_name: origin
+                git_push_fetch: false
+                save_file_name: cake.md
+                default_file_path: cake.md
+                skin_file_name: gruvbox.hjson
+                scrollview_threshold: -1
+            }"#,
+        )
+        .unwrap();
+        config.save_query("today", "-t work --started".to_string());
+        match parse_cli(&config, args("cake ls @today")).unwrap().command {
+            Some(Commands::List { selector, .. }) => {
+                assert_eq!(selector.tags, Some("work".to_string()));
+                assert!(selector.started);
+            }
+            _ => panic!("expected the list command"),
+        }
+        // the query command gets the names as they are
+        match parse_cli(&config, args("cake query delete @today"))
+            .unwrap()
+            .command
+        {
+            Some(Commands::Query {
+                command: QueryCommands::Delete { name },
+            }) => assert_eq!(name, "@today"),
+            _ => panic!("expected query delete"),
+        }
+        match parse_cli(&config, args("cake query save @today -t home"))
+            .unwrap()
+            .command
+        {
+            Some(Commands::Query {
+                command: QueryCommands::Save { name, selector },
+            }) => {
+                assert_eq!(name, "@today");
+                assert_eq!(selector, vec!["-t", "home"]);
+            }
+            _ => panic!("expected query save"),
+        }
+    }
+}
+
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
 pub struct Cli {
@@ -38,6 +98,15 @@ pub struct Cli {
     #[clap(short, long)]
     pub git: bool,
 
+    /// Confirm destructive commands like rm without asking,
+    /// see confirm_threshold in the config
+    #[clap(short, long, global = true)]
+    pub yes: bool,
+
+    /// Show the changes of the command without writing them, no git commit is made
+    #[clap(long, global = true)]
+    pub dry_run: bool,
+
     #[clap(subcommand)]
     pub command: Option<Commands>,
 }
@@ -79,227 +148,142 @@ pub enum Commands {
     /// remove items, alias rm
     #[clap(alias("rm"))]
     Remove {
-        /// Select by ids
-        #[clap(value_parser)]
-        ids: Option<String>,
-
-        /// Select by children
-        #[clap(short, long)]
-        children: Option<String>,
-
-        /// Select by parents
-        #[clap(short, long)]
-        parents: Option<String>,
-
-        /// Select by tags
-        /// use ~ to exclude tag e.g. -t ~some_tag
-        #[clap(short, long)]
-        tags: Option<String>,
-
-        /// Select by time before this duration from now
-        #[clap(short, long)]
-        before: Option<String>,
-
-        /// Select by time after this duration from now
-        #[clap(short, long)]
-        after: Option<String>,
-
-        /// Select started items
-        #[clap(long, action)]
-        started: bool,
-
-        /// Select stopped items
-        #[clap(long, action)]
-        stopped: bool,
-
-        /// recursive execution of the command.
-        /// e.g. -r: children, -rr parents, -rrr both
-        #[clap(short, long, action = clap::ArgAction::Count)]
-        recursive: u8,
-
-        /// Concatinate selectors with or instead of and
-        #[clap(long, action)]
-        or: bool,
+        #[clap(flatten)]
+        selector: SelectorArgs,
     },
     /// Tag items with selectors.
     /// Use ~tag to remove a tag.
     Tag {
-        /// Select by ids
-        #[clap(value_parser)]
-        ids: Option<String>,
+        #[clap(flatten)]
+        selector: SelectorArgs,
 
         /// comma separated tags which will be assigned
         #[clap(value_parser)]
         new_tags: Option<String>,
-
-        /// Select by children
-        #[clap(short, long)]
-        children: Option<String>,
-
-        /// Select by parents
-        #[clap(short, long)]
-        parents: Option<String>,
-
-        /// Select by tags
-        /// use ~ to exclude tag e.g. -t ~some_tag
-        #[clap(short, long)]
-        tags: Option<String>,
-
-        /// Select by time before this duration from now
-        #[clap(short, long)]
-        before: Option<String>,
-
-        /// Select by time after this duration from now
-        #[clap(short, long)]
-        after: Option<String>,
-
-        /// Select started items
-        #[clap(long, action)]
-        started: bool,
-
-        /// Select stopped items
-        #[clap(long, action)]
-        stopped: bool,
-
-        /// recursive execution of the command.
-        /// e.g. -r: children, -rr parents, -rrr both
-        #[clap(short, long, action = clap::ArgAction::Count)]
-        recursive: u8,
-
-        /// Concatinate selectors with or instead of and
-        #[clap(long, action)]
-        or: bool,
+    },
+    /// list all tags with usage, or rename, merge and delete tags in all items.
+    /// Namespaces are included, e.g. renaming a renames a/b
+    Tags {
+        #[clap(subcommand)]
+        command: Option<TagsCommands>,
     },
     /// start timetracking for item
     Start {
-        /// Select by ids
-        #[clap(value_parser)]
-        ids: Option<String>,
-
-        /// Select by children
-        #[clap(short, long)]
-        children: Option<String>,
+        #[clap(flatten)]
+        selector: SelectorArgs,
 
-        /// Select by parents
-        #[clap(short, long)]
-        parents: Option<String>,
-
-        /// Select by tags, use ~ to exclude tag e.g. -t ~some_tag
-        #[clap(short, long)]
-        tags: Option<String>,
-
-        /// Select by time before this duration from now
-        #[clap(short, long)]
-        before: Option<String>,
-
-        /// Select by time after this duration from now
-        #[clap(short, long)]
-        after: Option<String>,
-
-        /// Select started items
-        #[clap(long, action)]
-        started: bool,
+        /// start at this time instead of now, e.g. 20m (ago) or yesterday 14:00
+        #[clap(long, multiple_values = true, value_name = "TIME")]
+        at: Vec<String>,
 
-        /// Select stopped items
-        #[clap(long, action)]
-        stopped: bool,
-
-        /// recursive execution of the command. -r: children, -rr parents, -rrr both
-        #[clap(short, long, action = clap::ArgAction::Count)]
-        recursive: u8,
-
-        /// Concatinate selectors with or instead of and
-        #[clap(long, action)]
-        or: bool,
+        /// stop every other running item first
+        #[clap(long)]
+        switch: bool,
     },
     /// stop timetracking for item
     Stop {
-        /// Select by ids
-        #[clap(value_parser)]
-        ids: Option<String>,
-
-        /// Select by children
-        #[clap(short, long)]
-        children: Option<String>,
+        #[clap(flatten)]
+        selector: SelectorArgs,
 
-        /// Select by parents
-        #[clap(short, long)]
-        parents: Option<String>,
-
-        /// Select by tags, use ~ to exclude tag e.g. -t ~some_tag
-        #[clap(short, long)]
-        tags: Option<String>,
+        /// stop at this time instead of now, e.g. 20m (ago) or yesterday 14:00
+        #[clap(long, multiple_values = true, value_name = "TIME")]
+        at: Vec<String>,
+    },
+    /// add a completed interval to the timetrack, e.g. cake track c3e 1h30m --at yesterday 14:00
+    Track {
+        /// the item
+        #[clap(value_parser)]
+        id: String,
 
-        /// Select by time before this duration from now
-        #[clap(short, long)]
-        before: Option<String>,
+        /// tracked time, e.g. 1h30m
+        #[clap(value_parser)]
+        duration: String,
 
-        /// Select by time after this duration from now
-        #[clap(short, long)]
-        after: Option<String>,
+        /// start of the interval, e.g. yesterday 14:00. Without it, the interval ends now
+        #[clap(long, multiple_values = true, value_name = "TIME")]
+        at: Vec<String>,
+    },
+    /// list the time entries of an item, or edit, annotate and delete one of them
+    Time {
+        /// the item
+        #[clap(value_parser)]
+        id: String,
 
-        /// Select started items
-        #[clap(long, action)]
-        started: bool,
+        #[clap(subcommand)]
+        command: Option<TimeCommands>,
+    },
+    /// timesheet of the tracked time per day, the time of the subtrees is included.
+    /// Intervals crossing midnight are split between the days
+    Report {
+        #[clap(flatten)]
+        selector: SelectorArgs,
+
+        /// the week containing this day, e.g. "last week" or 2025-03-12. Default is this week
+        #[clap(
+            long,
+            value_name = "DAY",
+            min_values = 0,
+            default_missing_value = "this week"
+        )]
+        week: Option<String>,
 
-        /// Select stopped items
-        #[clap(long, action)]
-        stopped: bool,
+        /// any other time span instead of a week, e.g. "last month" or 2025-03-01..2025-03-15
+        #[clap(long, value_name = "SPAN", conflicts_with = "week")]
+        span: Option<String>,
 
-        /// recursive execution of the command. -r: children, -rr parents, -rrr both
-        #[clap(short, long, action = clap::ArgAction::Count)]
-        recursive: u8,
+        /// one row per selected item or per tag of the selected items
+        #[clap(long, value_enum, default_value_t)]
+        by: ReportBy,
 
-        /// Concatinate selectors with or instead of and
+        /// print comma separated values with hours instead of a table
         #[clap(long, action)]
-        or: bool,
+        csv: bool,
     },
     /// list items, alias ls
     #[clap(alias("ls"))]
     List {
-        /// Select by ids
-        #[clap(value_parser)]
-        ids: Option<String>,
-
-        /// Select by children
-        #[clap(short, long)]
-        children: Option<String>,
-
-        /// Select by parents
-        #[clap(short, long)]
-        parents: Option<String>,
-
-        /// Select by tags, use ~ to exclude tag e.g. -t ~some_tag
-        #[clap(short, long)]
-        tags: Option<String>,
-
-        /// Select by time before this duration from now
-        #[clap(short, long)]
-        before: Option<String>,
-
-        /// Select by time after this duration from now
-        #[clap(short, long)]
-        after: Option<String>,
-
-        /// Select started items
-        #[clap(long, action)]
-        started: bool,
-
-        /// Select stopped items
-        #[clap(long, action)]
-        stopped: bool,
-
-        /// recursive execution of the command. -r: children, -rr parents, -rrr both
-        #[clap(short, long, action = clap::ArgAction::Count)]
-        recursive: u8,
+        #[clap(flatten)]
+        selector: SelectorArgs,
 
         /// detailed presentation of the items
         #[clap(short, long, action)]
         long: bool,
 
-        /// Concatinate selectors with or instead of and
+        /// show the ancestor path of every item, e.g. cake › c3e › 70f
+        #[clap(long, action)]
+        path: bool,
+    },
+    /// fuzzy search for items by ids, tags and content, best matches first
+    Find {
+        /// search terms, all of them have to match
+        #[clap(value_parser, required = true)]
+        query: Vec<String>,
+
+        /// maximum number of results
+        #[clap(short, long, default_value_t = 10)]
+        number: usize,
+
+        /// print only the id of the best match, e.g. cake start $(cake find skin --pick)
         #[clap(long, action)]
-        or: bool,
+        pick: bool,
+    },
+    /// merge the item drop into keep. Content, tags, timetrack and
+    /// relations are combined, drop is removed afterwards
+    Merge {
+        /// the item which remains
+        #[clap(value_parser)]
+        keep: String,
+
+        /// the duplicate which is removed
+        #[clap(value_parser)]
+        drop: String,
+    },
+    /// split an item into children, one for every top-level heading
+    /// or, without headings, for every top-level bullet of the content
+    Split {
+        /// the item to split
+        #[clap(value_parser)]
+        id: String,
     },
     /// show a markdown file in terminal
     Show {
@@ -318,15 +302,125 @@ pub enum Commands {
         #[clap(long, action)]
         remote: bool,
     },
+    /// manage saved queries, use them as @name in every command which selects items
+    Query {
+        #[clap(subcommand)]
+        command: QueryCommands,
+    },
     /// Open config file in editor
     Config {},
 }
 
+#[derive(Subcommand)]
+pub enum TagsCommands {
+    /// list all tags with the number of items and the last modification, alias ls
+    #[clap(alias("ls"))]
+    List {},
+    /// rename a tag which is not used yet
+    Rename {
+        #[clap(value_parser)]
+        old: String,
+
+        #[clap(value_parser)]
+        new: String,
+    },
+    /// merge the tag from into the existing tag into
+    Merge {
+        #[clap(value_parser)]
+        from: String,
+
+        #[clap(value_parser)]
+        into: String,
+    },
+    /// delete a tag from all items, alias rm
+    #[clap(alias("rm"))]
+    Delete {
+        #[clap(value_parser)]
+        tag: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TimeCommands {
+    /// list the time entries with their number, alias ls
+    #[clap(alias("ls"))]
+    List {},
+    /// change start or end of an entry, e.g. cake time c3e edit 2 --end 14:30
+    Edit {
+        /// number of the entry, see cake time <id>
+        #[clap(value_parser)]
+        number: usize,
+
+        /// new start, e.g. 20m (ago) or yesterday 14:00
+        #[clap(long, multiple_values = true, value_name = "TIME")]
+        start: Vec<String>,
+
+        /// new end, formats like --start
+        #[clap(long, multiple_values = true, value_name = "TIME")]
+        end: Vec<String>,
+    },
+    /// describe an entry, an empty note removes it
+    Note {
+        /// number of the entry, see cake time <id>
+        #[clap(value_parser)]
+        number: usize,
+
+        #[clap(value_parser)]
+        note: Vec<String>,
+    },
+    /// delete an entry, alias rm
+    #[clap(alias("rm"))]
+    Delete {
+        /// number of the entry, see cake time <id>
+        #[clap(value_parser)]
+        number: usize,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum QueryCommands {
+    /// list saved queries, alias ls
+    #[clap(alias("ls"))]
+    List {},
+    /// save selector arguments under a name, e.g. cake query save today -t work --started
+    Save {
+        /// name of the query, used as @name
+        #[clap(value_parser)]
+        name: String,
+
+        /// selector arguments, separate or as one quoted string
+        #[clap(
+            value_parser,
+            required = true,
+            allow_hyphen_values = true,
+            multiple_values = true
+        )]
+        selector: Vec<String>,
+    },
+    /// delete a saved query, alias rm
+    #[clap(alias("rm"))]
+    Delete {
+        /// name of the query
+        #[clap(value_parser)]
+        name: String,
+    },
+}
+
 const FILETYPE_JSON: &str = ".json";
 const FILETYPE_MD: &str = ".md";
 
+/// saved queries are expanded before parsing, so @name works for every selector.
+/// The query command manages them by name and keeps its arguments
+fn parse_cli(config: &Config, args: Vec<String>) -> Result<Cli, Box<dyn Error>> {
+    match Cli::try_parse_from(&args) {
+        Ok(cli) if matches!(cli.command, Some(Commands::Query { .. })) => Ok(cli),
+        _ => Ok(Cli::try_parse_from(config.expand_queries(args)?)?),
+    }
+}
+
 pub fn run() -> Result<(), Box<dyn Error>> {
-    let cli = Cli::try_parse();
+    let mut config = Config::new()?;
+    let cli = parse_cli(&config, args().collect());
 
     if let Err(err) = cli {
         // TODO search for commands in settings
@@ -347,7 +441,6 @@ pub fn run() -> Result<(), Box<dyn Error>> {
     let args: Vec<String> = args().collect();
     debug(&format!("{:?}", args));
 
-    let config = Config::new()?;
     let mut current_branch = "".to_string();
     if git::is_repo() && !config.disable_git {
         current_branch = git::current_branch_name()?;
@@ -415,6 +508,16 @@ pub fn run() -> Result<(), Box<dyn Error>> {
         return Err("Only .md or .json files are supported".into());
     }
 
+    // a dry run compares the store before and after the command
+    let before = if cli.dry_run {
+        Some(store.clone())
+    } else {
+        None
+    };
+    // no need to confirm changes which are not written
+    let yes = cli.yes || cli.dry_run;
+    config.dry_run = cli.dry_run;
+
     // You can check for the existence of subcommands, and if found use their
     // matches just as you would the top level cmd
     match &cli.command {
@@ -426,132 +529,144 @@ pub fn run() -> Result<(), Box<dyn Error>> {
             tags,
             edit,
             overwrite,
-        }) => commands::add(
-            &debug,
-            &config,
-            &mut store,
-            Selector::new(
-                ids, children, parents, tags, &None, &None, &false, &false, &0, &false,
-            )?,
-            message.to_owned().unwrap_or("".to_string()),
-            args[1] == "edit" || *edit, // auto edit flag only works if no flags are used before...
-            *overwrite,
-        )?,
-        Some(Commands::Remove {
-            ids,
-            children,
-            parents,
-            tags,
-            before,
-            after,
-            started,
-            stopped,
-            recursive,
-            or,
-        }) => commands::remove(
-            debug,
-            &mut &mut store,
-            Selector::new(
-                ids, children, parents, tags, before, after, started, stopped, recursive, or,
-            )?,
-        )?,
-        Some(Commands::Tag {
-            ids,
-            new_tags,
-            children,
-            parents,
-            tags,
-            before,
-            after,
-            started,
-            stopped,
-            recursive,
-            or,
         }) => {
-            let mut i = ids;
-            let mut nt = new_tags;
+            let selector = Selector::new(
+                &SelectorArgs {
+                    ids: ids.to_owned(),
+                    children: children.to_owned(),
+                    parents: parents.to_owned(),
+                    tags: tags.to_owned(),
+                    ..Default::default()
+                },
+                &store,
+            )?;
+            commands::add(
+                debug,
+                &config,
+                &mut store,
+                selector,
+                message.to_owned().unwrap_or("".to_string()),
+                args[1] == "edit" || *edit, // auto edit flag only works if no flags are used before...
+                *overwrite,
+            )?
+        }
+        Some(Commands::Remove { selector }) => {
+            let selector = Selector::new(selector, &store)?;
+            commands::remove(debug, &config, &mut store, selector, yes)?
+        }
+        Some(Commands::Tag { selector, new_tags }) => {
+            let mut selector = selector.clone();
+            let mut nt = new_tags.to_owned();
             // use ids for tags if only one option is set.
-            if ids.is_none() && new_tags.is_none() {
+            if selector.ids.is_none() && new_tags.is_none() {
                 return Err("You have to specify tags. [ids selector, optional] [tags]".into());
             } else if new_tags.is_none() {
-                nt = ids;
-                i = new_tags;
+                nt = selector.ids.take();
             }
-            commands::tag(
-                debug,
-                &mut &mut store,
-                Selector::new(
-                    i, children, parents, tags, before, after, started, stopped, recursive, or,
-                )?,
-                nt,
-            )?
+            let selector = Selector::new(&selector, &store)?;
+            commands::tag(debug, &config, &mut store, selector, &nt, yes)?
         }
+        Some(Commands::Tags { command }) => match command {
+            None | Some(TagsCommands::List {}) => commands::tags_list(debug, &store)?,
+            Some(TagsCommands::Rename { old, new }) => {
+                commands::tags_rename(debug, &config, &mut store, old, new, yes)?
+            }
+            Some(TagsCommands::Merge { from, into }) => {
+                commands::tags_merge(debug, &config, &mut store, from, into, yes)?
+            }
+            Some(TagsCommands::Delete { tag }) => {
+                commands::tags_delete(debug, &config, &mut store, tag, yes)?
+            }
+        },
         Some(Commands::Start {
-            ids,
-            children,
-            parents,
-            tags,
-            before,
-            after,
-            started,
-            stopped,
-            recursive,
-            or,
-        }) => commands::start(
-            debug,
-            &mut &mut store,
-            Selector::new(
-                ids, children, parents, tags, before, after, started, stopped, recursive, or,
-            )?,
-        )?,
-        Some(Commands::Stop {
-            ids,
-            children,
-            parents,
-            tags,
-            before,
-            after,
-            started,
-            stopped,
-            recursive,
-            or,
-        }) => commands::stop(
-            debug,
-            &mut &mut store,
-            Selector::new(
-                ids, children, parents, tags, before, after, started, stopped, recursive, or,
-            )?,
-        )?,
+            selector,
+            at,
+            switch,
+        }) => {
+            let selector = Selector::new(selector, &store)?;
+            commands::start(debug, &config, &mut store, selector, at, *switch)?
+        }
+        Some(Commands::Stop { selector, at }) => {
+            let selector = Selector::new(selector, &store)?;
+            commands::stop(debug, &config, &mut store, selector, at)?
+        }
+        Some(Commands::Track { id, duration, at }) => {
+            let id = store.resolve(id)?;
+            commands::track(debug, &config, &mut store, &id, duration, at)?
+        }
+        Some(Commands::Time { id, command }) => {
+            let id = store.resolve(id)?;
+            match command {
+                None | Some(TimeCommands::List {}) => commands::time_list(debug, &store, &id)?,
+                Some(TimeCommands::Edit { number, start, end }) => {
+                    commands::time_edit(debug, &config, &mut store, &id, *number, start, end)?
+                }
+                Some(TimeCommands::Note { number, note }) => {
+                    commands::time_note(debug, &config, &mut store, &id, *number, &note.join(" "))?
+                }
+                Some(TimeCommands::Delete { number }) => {
+                    commands::time_delete(debug, &config, &mut store, &id, *number)?
+                }
+            }
+        }
+        Some(Commands::Report {
+            selector,
+            week,
+            span,
+            by,
+            csv,
+        }) => {
+            let selector = Selector::new(selector, &store)?;
+            let span = match span {
+                Some(span) => util::parse_time_span(span)?.ok_or("Missing time span")?,
+                None => util::parse_week(week.as_deref().unwrap_or("this week"))?,
+            };
+            commands::report(debug, &config, &store, selector, span, *by, *csv)?
+        }
         Some(Commands::List {
-            ids,
-            children,
-            parents,
-            tags,
-            before,
-            after,
-            started,
-            stopped,
-            recursive,
+            selector,
             long,
-            or,
+            path,
         }) => commands::list(
             debug,
             &config,
-            &mut store,
-            Selector::new(
-                ids, children, parents, tags, before, after, started, stopped, recursive, or,
-            )?,
+            &store,
+            Selector::new(selector, &store)?,
             *long,
+            *path,
         )?,
+        Some(Commands::Find {
+            query,
+            number,
+            pick,
+        }) => commands::find(debug, &config, &store, query, *number, *pick)?,
+        Some(Commands::Merge { keep, drop }) => {
+            let keep = store.resolve(keep)?;
+            let drop = store.resolve(drop)?;
+            commands::merge(debug, &config, &mut store, &keep, &drop, yes)?
+        }
+        Some(Commands::Split { id }) => {
+            let id = store.resolve(id)?;
+            commands::split(debug, &config, &mut store, &id)?
+        }
         Some(Commands::Show { path }) => commands::show(debug, &config, path)?,
         Some(Commands::Init { git, remote }) => commands::init(debug, &config, *git, *remote)?,
+        Some(Commands::Query { command }) => match command {
+            QueryCommands::List {} => commands::query_list(debug, &config)?,
+            QueryCommands::Save { name, selector } => {
+                commands::query_save(debug, &mut config, &store, name, selector)?
+            }
+            QueryCommands::Delete { name } => commands::query_delete(debug, &mut config, name)?,
+        },
         Some(Commands::Config {}) => commands::config(debug, &config)?,
         None => {
             println!("Nothing happed o.0");
         }
     }
 
-    if output_file.ends_with(FILETYPE_MD) {
+    if let Some(before) = &before {
+        commands::print_diff(before, &store);
+    } else if output_file.ends_with(FILETYPE_MD) {
         store.write_md(&output_file)?;
     } else if output_file.ends_with(FILETYPE_JSON) {
         store.write_json(&output_file)?;
@@ -561,22 +676,25 @@ pub fn run() -> Result<(), Box<dyn Error>> {
     }
     if git::is_repo() && !config.disable_git {
         if git::check_if_branch_exists(&config)? {
-            debug(&format!("git add {:?}", config.save_file_name));
-            git::add(&config)?;
-            debug(&format!("git commit"));
-            let args: String = std::env::args().collect::<Vec<_>>().join(" ");
-            git::commit(&args)?;
-            if config.git_push_fetch || cli.git {
-                if !git::check_if_remote_branch_exists(&config)? {
-                    return Err(Box::from(
-                        "There is no remote branch for '".to_owned()
-                            + &config.git_branch_name
-                            + "'. You need to set it yourself. Abort...",
-                    ));
+            // a dry run writes nothing to commit
+            if before.is_none() {
+                debug(&format!("git add {:?}", config.save_file_name));
+                git::add(&config)?;
+                debug("git commit");
+                let args: String = std::env::args().collect::<Vec<_>>().join(" ");
+                git::commit(&args)?;
+                if config.git_push_fetch || cli.git {
+                    if !git::check_if_remote_branch_exists(&config)? {
+                        return Err(Box::from(
+                            "There is no remote branch for '".to_owned()
+                                + &config.git_branch_name
+                                + "'. You need to set it yourself. Abort...",
+                        ));
+                    }
+                    println!("{}", "Pushing...".to_string().grey().italic());
+                    debug("git push");
+                    git::push(&config)?;
                 }
-                println!("{}", "Pushing...".to_string().grey().italic());
-                debug(&format!("git push"));
-                git::push(&config)?;
             }
             debug(&format!("git checkout {:?}", current_branch));
             git::checkout_branch(&current_branch)?;
@@ -584,6 +702,8 @@ pub fn run() -> Result<(), Box<dyn Error>> {
             git::stash_pop()?;
         }
     }
-    config.write_json_if_dirty()?;
+    if before.is_none() {
+        config.write_json_if_dirty()?;
+    }
     Ok(())
 }
@@ -0,0 +1,336 @@
+use crate::error::ParseError;
+use crate::item::Item;
+use crate::util;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Query::parse("tag:cake").unwrap(),
+            Query::Atom(Predicate::Tag("cake".to_string()))
+        );
+        assert_eq!(
+            Query::parse("tag:cake and (started or parent:c3e) and not tag:wichtig").unwrap(),
+            Query::And(
+                Box::new(Query::And(
+                    Box::new(Query::Atom(Predicate::Tag("cake".to_string()))),
+                    Box::new(Query::Or(
+                        Box::new(Query::Atom(Predicate::Started)),
+                        Box::new(Query::Atom(Predicate::Parent("c3e".to_string())))
+                    ))
+                )),
+                Box::new(Query::Not(Box::new(Query::Atom(Predicate::Tag(
+                    "wichtig".to_string()
+                )))))
+            )
+        );
+        // and binds stronger than or
+        assert_eq!(
+            Query::parse("started OR tag:a AND tag:b").unwrap(),
+            Query::Or(
+                Box::new(Query::Atom(Predicate::Started)),
+                Box::new(Query::And(
+                    Box::new(Query::Atom(Predicate::Tag("a".to_string()))),
+                    Box::new(Query::Atom(Predicate::Tag("b".to_string())))
+                ))
+            )
+        );
+        assert_eq!(
+            Query::parse("grep:\"skin file\"").unwrap(),
+            Query::Atom(Predicate::Grep("skin file".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Query::parse("tag:cake and (started").unwrap_err().message,
+            "Expected ')' at position 22\ntag:cake and (started\n                     ^"
+        );
+        assert_eq!(
+            Query::parse("tag:cake or").unwrap_err().message,
+            "Expected a condition at position 12\ntag:cake or\n           ^"
+        );
+        assert_eq!(
+            Query::parse("color:red").unwrap_err().message,
+            "Unknown condition 'color:red' at position 1\ncolor:red\n^"
+        );
+        assert_eq!(
+            Query::parse("started)").unwrap_err().message,
+            "Unexpected ')' at position 8\nstarted)\n       ^"
+        );
+        assert!(Query::parse("before:1x").is_err());
+        assert_eq!(
+            Query::parse("before:\" \"").unwrap_err().message,
+            "Invalid value in 'before: ': Missing time at position 1\nbefore:\" \"\n^"
+        );
+        assert!(Query::parse("after:\" \"").is_err());
+        assert!(Query::parse("modified:\" \"").is_err());
+        assert!(Query::parse("worked:\" \"").is_err());
+        assert!(Query::parse("worked:").is_err());
+        assert!(Query::parse("grep:\"open").is_err());
+    }
+}
+
+/// a single condition of a query, e.g. tag:cake or started
+#[derive(Debug, PartialEq, Eq)]
+pub enum Predicate {
+    Id(String),
+    Tag(String),
+    Parent(String),
+    Child(String),
+    /// creation time before this timestamp
+    Before(i64),
+    /// creation time at or after this timestamp
+    After(i64),
+    /// modification time at or after this timestamp
+    Modified(i64),
+    /// tracked time overlapping this time span
+    Worked(i64, i64),
+    /// case insensitive text in the content
+    Grep(String),
+    Started,
+    Stopped,
+}
+
+/// predicate tree of a boolean query like
+/// tag:cake and (started or parent:c3e) and not tag:wichtig
+#[derive(Debug, PartialEq, Eq)]
+pub enum Query {
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+    Not(Box<Query>),
+    Atom(Predicate),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Token {
+    LParen,
+    RParen,
+    And,
+    Or,
+    Not,
+    Word(String),
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    // tokens with their character position in the input
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Query, ParseError> {
+        let mut parser = Parser {
+            input,
+            tokens: tokenize(input)?,
+            pos: 0,
+        };
+        let query = parser.parse_or()?;
+        match parser.tokens.get(parser.pos) {
+            Some((Token::RParen, p)) => Err(error_at(input, *p, "Unexpected ')'")),
+            Some((_, p)) => Err(error_at(input, *p, "Expected 'and' or 'or'")),
+            None => Ok(query),
+        }
+    }
+
+    pub fn matches(&self, item: &Item) -> bool {
+        match self {
+            Query::And(a, b) => a.matches(item) && b.matches(item),
+            Query::Or(a, b) => a.matches(item) || b.matches(item),
+            Query::Not(a) => !a.matches(item),
+            Query::Atom(p) => p.matches(item),
+        }
+    }
+}
+
+impl Predicate {
+    fn parse(word: &str) -> Option<Result<Predicate, Box<dyn std::error::Error>>> {
+        // a blank value like " " is no time span
+        let span = |value: &str| {
+            util::parse_time_span(value).and_then(|t| t.ok_or_else(|| "Missing time".into()))
+        };
+        let p = match word.split_once(':') {
+            None => match word.to_lowercase().as_str() {
+                "started" => Predicate::Started,
+                "stopped" => Predicate::Stopped,
+                _ => return None,
+            },
+            Some((key, value)) => match key.to_lowercase().as_str() {
+                "id" => Predicate::Id(value.to_string()),
+                "tag" => Predicate::Tag(value.to_string()),
+                "parent" => Predicate::Parent(value.to_string()),
+                "child" => Predicate::Child(value.to_string()),
+                "grep" => Predicate::Grep(value.to_lowercase()),
+                "before" => match span(value) {
+                    Ok((_, end)) => Predicate::Before(end),
+                    Err(e) => return Some(Err(e)),
+                },
+                "after" => match span(value) {
+                    Ok((start, _)) => Predicate::After(start),
+                    Err(e) => return Some(Err(e)),
+                },
+                "modified" => match span(value) {
+                    Ok((start, _)) => Predicate::Modified(start),
+                    Err(e) => return Some(Err(e)),
+                },
+                "worked" => match span(value) {
+                    Ok((start, end)) => Predicate::Worked(start, end),
+                    Err(e) => return Some(Err(e)),
+                },
+                _ => return None,
+            },
+        };
+        Some(Ok(p))
+    }
+
+    fn matches(&self, item: &Item) -> bool {
+        match self {
+            Predicate::Id(id) => item.id() == id,
+            Predicate::Tag(tag) => item.has_tag(tag),
+            Predicate::Parent(id) => item.parents().contains(id),
+            Predicate::Child(id) => item.children().contains(id),
+            Predicate::Before(t) => item.timestamp() < *t,
+            Predicate::After(t) => item.timestamp() >= *t,
+            Predicate::Modified(t) => item.last_modified() >= *t,
+            Predicate::Worked(start, end) => item.worked_between(*start, *end),
+            Predicate::Grep(s) => item.content().to_lowercase().contains(s),
+            Predicate::Started => item.is_started(),
+            Predicate::Stopped => item.is_stopped(),
+        }
+    }
+}
+
+impl Parser<'_> {
+    fn next_is(&self, token: Token) -> bool {
+        matches!(self.tokens.get(self.pos), Some((t, _)) if *t == token)
+    }
+
+    // or_expr := and_expr ("or" and_expr)*
+    fn parse_or(&mut self) -> Result<Query, ParseError> {
+        let mut left = self.parse_and()?;
+        while self.next_is(Token::Or) {
+            self.pos += 1;
+            left = Query::Or(Box::new(left), Box::new(self.parse_and()?));
+        }
+        Ok(left)
+    }
+
+    // and_expr := not_expr ("and" not_expr)*
+    fn parse_and(&mut self) -> Result<Query, ParseError> {
+        let mut left = self.parse_not()?;
+        while self.next_is(Token::And) {
+            self.pos += 1;
+            left = Query::And(Box::new(left), Box::new(self.parse_not()?));
+        }
+        Ok(left)
+    }
+
+    // not_expr := "not" not_expr | "(" or_expr ")" | condition
+    fn parse_not(&mut self) -> Result<Query, ParseError> {
+        let (token, p) = match self.tokens.get(self.pos) {
+            Some((t, p)) => (t, *p),
+            None => {
+                return Err(error_at(
+                    self.input,
+                    self.input.chars().count() + 1,
+                    "Expected a condition",
+                ))
+            }
+        };
+        self.pos += 1;
+        match token {
+            Token::Not => Ok(Query::Not(Box::new(self.parse_not()?))),
+            Token::LParen => {
+                let query = self.parse_or()?;
+                if !self.next_is(Token::RParen) {
+                    let p = match self.tokens.get(self.pos) {
+                        Some((_, p)) => *p,
+                        None => self.input.chars().count() + 1,
+                    };
+                    return Err(error_at(self.input, p, "Expected ')'"));
+                }
+                self.pos += 1;
+                Ok(query)
+            }
+            Token::Word(w) => match Predicate::parse(w) {
+                Some(Ok(predicate)) => Ok(Query::Atom(predicate)),
+                Some(Err(e)) => Err(error_at(
+                    self.input,
+                    p,
+                    &format!("Invalid value in '{}': {}", w, e),
+                )),
+                None => Err(error_at(
+                    self.input,
+                    p,
+                    &format!("Unknown condition '{}'", w),
+                )),
+            },
+            _ => Err(error_at(self.input, p, "Expected a condition")),
+        }
+    }
+}
+
+/// splits the input into parentheses, operators and words,
+/// double quotes group words with spaces
+fn tokenize(input: &str) -> Result<Vec<(Token, usize)>, ParseError> {
+    let mut tokens = vec![];
+    let mut chars = input.chars().enumerate().peekable();
+    while let Some((i, c)) = chars.next() {
+        // positions are shown starting with 1
+        let p = i + 1;
+        match c {
+            '(' => tokens.push((Token::LParen, p)),
+            ')' => tokens.push((Token::RParen, p)),
+            c if c.is_whitespace() => (),
+            _ => {
+                let mut word = String::new();
+                let mut quoted = c == '"';
+                if !quoted {
+                    word.push(c);
+                }
+                while let Some((_, c)) = chars.peek() {
+                    if !quoted && (c.is_whitespace() || *c == '(' || *c == ')') {
+                        break;
+                    }
+                    let (_, c) = chars.next().unwrap();
+                    if c == '"' {
+                        quoted = !quoted;
+                    } else {
+                        word.push(c);
+                    }
+                }
+                if quoted {
+                    return Err(error_at(input, p, "Unclosed '\"'"));
+                }
+                tokens.push((
+                    match word.to_lowercase().as_str() {
+                        "and" => Token::And,
+                        "or" => Token::Or,
+                        "not" => Token::Not,
+                        _ => Token::Word(word),
+                    },
+                    p,
+                ));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// error message with a marker below the position in the input
+fn error_at(input: &str, position: usize, message: &str) -> ParseError {
+    ParseError {
+        message: format!(
+            "{} at position {}\n{}\n{}^",
+            message,
+            position,
+            input,
+            " ".repeat(position - 1)
+        ),
+    }
+}
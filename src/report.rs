@@ -0,0 +1,250 @@
+use crate::store::Store;
+use crate::util;
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, HashSet};
+use std::error::Error;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::Item;
+    use chrono::{Local, TimeZone};
+
+    /// parent p with the child c and the root q, tracked on two days
+    fn store() -> Store {
+        let at = |d, h, m| {
+            Local
+                .with_ymd_and_hms(2025, 3, d, h, m, 0)
+                .unwrap()
+                .timestamp()
+        };
+        let mut store = Store::default();
+        let mut p = Item::new("p".to_string(), vec![], vec![], vec!["work".to_string()]);
+        p.set_content("# Parent".to_string());
+        p.track(at(10, 8, 0), at(10, 9, 0)).unwrap();
+        store.add(p).unwrap();
+        let mut c = Item::new("c".to_string(), vec![], vec!["p".to_string()], vec![]);
+        c.set_content("# Child".to_string());
+        c.track(at(11, 10, 0), at(11, 10, 30)).unwrap();
+        store.add(c).unwrap();
+        let mut q = Item::new("q".to_string(), vec![], vec![], vec![]);
+        q.set_content("# Say \"hi\", then go".to_string());
+        q.track(at(10, 12, 0), at(10, 12, 15)).unwrap();
+        store.add(q).unwrap();
+        store
+    }
+
+    fn csv(by: ReportBy) -> String {
+        let span = |d| {
+            Local
+                .with_ymd_and_hms(2025, 3, d, 0, 0, 0)
+                .unwrap()
+                .timestamp()
+        };
+        let ids = ["p", "c", "q"].map(String::from);
+        Report::new(&store(), &ids, by, span(10), span(12))
+            .unwrap()
+            .to_csv(by)
+    }
+
+    #[test]
+    fn test_by_item() {
+        // the parent includes its child, the total counts the child once
+        assert_eq!(
+            csv(ReportBy::Item),
+            "id,title,2025-03-10,2025-03-11,total\n\
+             p,Parent,1.00,0.50,1.50\n\
+             c,Child,0.00,0.50,0.50\n\
+             q,\"Say \"\"hi\"\", then go\",0.25,0.00,0.25\n\
+             total,,1.25,0.50,1.75\n"
+        );
+    }
+
+    #[test]
+    fn test_by_tag() {
+        assert_eq!(
+            csv(ReportBy::Tag),
+            "tag,2025-03-10,2025-03-11,total\n\
+             (untagged),0.25,0.50,0.75\n\
+             work,1.00,0.50,1.50\n\
+             total,1.25,0.50,1.75\n"
+        );
+    }
+}
+
+/// label of the row for items without tags
+const UNTAGGED: &str = "(untagged)";
+const TOTAL: &str = "total";
+
+/// rows of the timesheet
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReportBy {
+    #[default]
+    Item,
+    Tag,
+}
+
+/// tracked time per row and day, every row includes the subtrees of its items
+pub struct Report {
+    days: Vec<NaiveDate>,
+    rows: Vec<Row>,
+    /// every item is counted once, even if it is part of several rows
+    total: Vec<i64>,
+}
+
+struct Row {
+    label: String,
+    title: String,
+    /// seconds per day
+    spent: Vec<i64>,
+}
+
+impl Report {
+    /// aggregates the time of the items and their descendants between start and end,
+    /// rows without time are left out
+    pub fn new(
+        store: &Store,
+        ids: &[String],
+        by: ReportBy,
+        start: i64,
+        end: i64,
+    ) -> Result<Report, Box<dyn Error>> {
+        let days = util::split_days(start, end)?;
+        let subtree = |id: &String| {
+            let mut res = store.descendants(id);
+            res.insert(id.to_string());
+            res
+        };
+        let groups = match by {
+            ReportBy::Item => ids
+                .iter()
+                .map(|id| {
+                    let title = store.get_item(id).map(|i| i.title()).unwrap_or_default();
+                    (id.to_string(), title.to_string(), subtree(id))
+                })
+                .collect::<Vec<_>>(),
+            ReportBy::Tag => {
+                let mut tags = BTreeMap::<String, HashSet<String>>::new();
+                for id in ids {
+                    let item = store.get_item(id).ok_or("Could not found id")?;
+                    if item.tags().is_empty() {
+                        tags.entry(UNTAGGED.to_string())
+                            .or_default()
+                            .extend(subtree(id));
+                    }
+                    for tag in item.tags() {
+                        tags.entry(tag.to_string()).or_default().extend(subtree(id));
+                    }
+                }
+                tags.into_iter()
+                    .map(|(tag, ids)| (tag, "".to_string(), ids))
+                    .collect()
+            }
+        };
+        let spent = |ids: &HashSet<String>| {
+            days.iter()
+                .map(|(_, from, to)| {
+                    ids.iter()
+                        .filter_map(|id| store.get_item(id))
+                        .map(|i| i.spent_between(*from, *to))
+                        .sum()
+                })
+                .collect::<Vec<i64>>()
+        };
+        let all = groups
+            .iter()
+            .flat_map(|(_, _, ids)| ids.iter().cloned())
+            .collect::<HashSet<String>>();
+        Ok(Report {
+            days: days.iter().map(|(day, _, _)| *day).collect(),
+            rows: groups
+                .iter()
+                .map(|(label, title, ids)| Row {
+                    label: label.to_string(),
+                    title: title.to_string(),
+                    spent: spent(ids),
+                })
+                .filter(|row| row.spent.iter().any(|s| *s > 0))
+                .collect(),
+            total: spent(&all),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// table with a column for every day and the sum of the row at the end
+    pub fn to_markdown(&self) -> String {
+        let hms = |spent: &[i64]| {
+            spent
+                .iter()
+                .chain([spent.iter().sum()].iter())
+                .map(|s| format!(" {} |", util::timestamp_to_hms(*s)))
+                .collect::<String>()
+        };
+        let mut res = format!(
+            "| |{} {} |\n|:-|{}\n",
+            self.days
+                .iter()
+                .map(|d| format!(" {} |", d.format("%a %m-%d")))
+                .collect::<String>(),
+            TOTAL,
+            "-:|".repeat(self.days.len() + 1)
+        );
+        for row in &self.rows {
+            res += &format!(
+                "| {} |{}\n",
+                format!("{} {}", row.label, row.title).trim(),
+                hms(&row.spent)
+            );
+        }
+        res += &format!("| **{}** |{}\n", TOTAL, hms(&self.total));
+        res
+    }
+
+    /// comma separated values with the time in hours and the date of every day in the header
+    pub fn to_csv(&self, by: ReportBy) -> String {
+        let hours = |spent: &[i64]| {
+            spent
+                .iter()
+                .chain([spent.iter().sum()].iter())
+                .map(|s| format!(",{:.2}", *s as f64 / 3600.0))
+                .collect::<String>()
+        };
+        let mut res = format!(
+            "{}{},{}\n",
+            match by {
+                ReportBy::Item => "id,title",
+                ReportBy::Tag => "tag",
+            },
+            self.days
+                .iter()
+                .map(|d| format!(",{}", d.format("%Y-%m-%d")))
+                .collect::<String>(),
+            TOTAL
+        );
+        for row in &self.rows {
+            res += &match by {
+                ReportBy::Item => format!("{},{}", csv_field(&row.label), csv_field(&row.title)),
+                ReportBy::Tag => csv_field(&row.label),
+            };
+            res += &format!("{}\n", hours(&row.spent));
+        }
+        res += TOTAL;
+        if by == ReportBy::Item {
+            res += ",";
+        }
+        res += &format!("{}\n", hours(&self.total));
+        res
+    }
+}
+
+/// quotes the field if it contains a comma, quote or line break
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
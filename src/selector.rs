@@ -1,7 +1,170 @@
+use crate::item::{Item, Sort, SortKey};
+use crate::query::Query;
 use crate::store::{Store, MAX_DEPTH};
 use crate::util;
+use clap::{Args, Parser};
+use regex::Regex;
 use std::error::Error;
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exclude_unknown_id() {
+        let mut store = Store::default();
+        store
+            .add(Item::new("a1".to_string(), vec![], vec![], vec![]))
+            .unwrap();
+        store
+            .add(Item::new(
+                "b2".to_string(),
+                vec![],
+                vec!["a1".to_string()],
+                vec![],
+            ))
+            .unwrap();
+        let args =
+            SelectorArgs::try_parse_from(&["a1,~zzz".to_string(), "-r".to_string()]).unwrap();
+        let selector = Selector::new(&args, &store).unwrap();
+        assert_eq!(selector.get(&store, true), vec!["a1", "b2"]);
+        let args = SelectorArgs::try_parse_from(&["a1,~b2".to_string(), "-r".to_string()]).unwrap();
+        let selector = Selector::new(&args, &store).unwrap();
+        assert_eq!(selector.get(&store, true), vec!["a1"]);
+    }
+}
+
+/// command line options shared by all commands which select items
+#[derive(Args, Clone, Debug, Default)]
+pub struct SelectorArgs {
+    /// Select by ids, use ~ to exclude ids e.g. ~c3e, with -r the whole subtree
+    #[clap(value_parser)]
+    pub ids: Option<String>,
+
+    /// Select by children, use ~ to exclude parents of a child e.g. -c ~c3e
+    #[clap(short, long)]
+    pub children: Option<String>,
+
+    /// Select by parents, use ~ to exclude children of a parent e.g. -p ~c3e
+    #[clap(short, long)]
+    pub parents: Option<String>,
+
+    /// Select by tags, use ~ to exclude tag e.g. -t ~some_tag.
+    /// A tag matches also the tags in its namespace, e.g. client-a matches client-a/backend
+    #[clap(short, long)]
+    pub tags: Option<String>,
+
+    /// Select by creation before this time, e.g. 1w2d (ago), yesterday, last monday,
+    /// 2025-03-01 14:00, 2025-03 or 2025-01..2025-03. The given day or span is included
+    #[clap(short, long)]
+    pub before: Option<String>,
+
+    /// Select by creation after this time, formats like --before
+    #[clap(short, long)]
+    pub after: Option<String>,
+
+    /// Select by modification since this time, formats like --before
+    #[clap(long, value_name = "TIME")]
+    pub modified_since: Option<String>,
+
+    /// Select items with tracked time since this time, formats like --before
+    #[clap(long, value_name = "TIME")]
+    pub worked_since: Option<String>,
+
+    /// Select items with tracked time overlapping this time span,
+    /// e.g. "last week", 2025-03 or 2025-03-01..2025-03-15
+    #[clap(long, value_name = "SPAN")]
+    pub worked_between: Option<String>,
+
+    /// Select items with more tracked time than this duration, e.g. 2h or 1h30m
+    #[clap(long, value_name = "DURATION")]
+    pub spent_more: Option<String>,
+
+    /// Select items with less tracked time than this duration, e.g. 10m
+    #[clap(long, value_name = "DURATION")]
+    pub spent_less: Option<String>,
+
+    /// Count only the tracked time in this time span for --spent-more and --spent-less,
+    /// formats like --worked-between
+    #[clap(long, value_name = "SPAN")]
+    pub spent_in: Option<String>,
+
+    /// Select started items
+    #[clap(long, action)]
+    pub started: bool,
+
+    /// Select stopped items
+    #[clap(long, action)]
+    pub stopped: bool,
+
+    /// recursive execution of the command. -r: children, -rr parents, -rrr both
+    #[clap(short, long, action = clap::ArgAction::Count)]
+    pub recursive: u8,
+
+    /// Select by case insensitive text in the content
+    #[clap(long, value_name = "PATTERN")]
+    pub grep: Option<String>,
+
+    /// Interpret the grep pattern as regular expression
+    #[clap(long, action, requires = "grep")]
+    pub regex: bool,
+
+    /// Select by a boolean expression, e.g.
+    /// "tag:cake and (started or parent:c3e) and not tag:wichtig".
+    /// Conditions: id:, tag:, parent:, child:, before:, after:, modified:, worked:, grep:,
+    /// started, stopped
+    #[clap(short, long, value_name = "EXPR")]
+    pub query: Option<String>,
+
+    /// maximum number of levels the recursive execution descends
+    #[clap(long, value_name = "N")]
+    pub depth: Option<usize>,
+
+    /// Concatinate selectors with or instead of and
+    #[clap(long, action)]
+    pub or: bool,
+
+    /// order of the items, on every level for recursive lists.
+    /// Equal items are ordered by id
+    #[clap(long, value_enum, default_value_t = SortKey::Created)]
+    pub sort: SortKey,
+
+    /// reverse the order of --sort
+    #[clap(long, action)]
+    pub reverse: bool,
+
+    /// maximum number of selected items after sorting, children and parents
+    /// of recursive execution are not counted
+    #[clap(long, value_name = "N")]
+    pub limit: Option<usize>,
+
+    /// skip the first N selected items after sorting
+    #[clap(long, value_name = "N", default_value_t = 0)]
+    pub offset: usize,
+
+    /// select only the first item after sorting, like --limit 1
+    #[clap(long, action, conflicts_with_all = &["limit", "offset", "last"])]
+    pub first: bool,
+
+    /// select only the last item after sorting, e.g. --sort modified --last
+    #[clap(long, action, conflicts_with_all = &["limit", "offset"])]
+    pub last: bool,
+}
+
+// parser for selector arguments without a command, e.g. of saved queries
+#[derive(Parser)]
+#[clap(no_binary_name = true)]
+struct SelectorParser {
+    #[clap(flatten)]
+    selector: SelectorArgs,
+}
+
+impl SelectorArgs {
+    pub fn try_parse_from(args: &[String]) -> Result<SelectorArgs, clap::Error> {
+        Ok(SelectorParser::try_parse_from(args)?.selector)
+    }
+}
+
 // default filter
 #[derive(Debug)]
 pub struct Selector {
@@ -10,45 +173,115 @@ pub struct Selector {
     pub parents: Vec<String>,
     pub tags: Vec<String>,
     pub exclude_tags: Vec<String>,
-    pub before: Option<i64>, // time in seconds relative to now
-    pub after: Option<i64>,  // time in seconds relative to now
+    pub exclude_ids: Vec<String>, // with recursion for children including their subtrees
+    pub exclude_children: Vec<String>,
+    pub exclude_parents: Vec<String>,
+    pub before: Option<i64>, // exclusive end of the given time
+    pub after: Option<i64>,  // start of the given time
+    pub modified_since: Option<i64>,
+    pub worked_between: Option<(i64, i64)>, // also used for worked_since with open end
+    pub spent_more: Option<i64>,            // seconds
+    pub spent_less: Option<i64>,            // seconds
+    pub spent_in: (i64, i64),               // time span for spent_more and spent_less
     pub started: bool,
     pub stopped: bool,
-    pub rparents: bool,  // recursive for parents
-    pub rchildren: bool, // recursive for children
-    or: bool,            // use or concatination of selectors
+    pub grep: Option<Regex>, // matches title and content
+    pub query: Option<Query>,
+    pub rparents: bool,   // recursive for parents
+    pub rchildren: bool,  // recursive for children
+    pub max_depth: usize, // depth limit for recursive_execute, selected items are at depth 0
+    pub sort: Sort,
+    limit: Option<usize>,
+    offset: usize,
+    last: bool, // select only the last item
+    or: bool,   // use or concatination of selectors
 }
 
 impl Selector {
-    pub fn new(
-        ids: &Option<String>,
-        children: &Option<String>,
-        parents: &Option<String>,
-        tags: &Option<String>,
-        before: &Option<String>,
-        after: &Option<String>,
-        started: &bool,
-        stopped: &bool,
-        recursive: &u8,
-        or: &bool,
-    ) -> Result<Selector, Box<dyn Error>> {
+    /// ids, children and parents may be given as paths, see Store::resolve
+    pub fn new(args: &SelectorArgs, store: &Store) -> Result<Selector, Box<dyn Error>> {
+        let rchildren = args.recursive == 1 || args.recursive > 2; // -r only children, -rrr both
+        let ids = args.ids.to_owned().unwrap_or("".to_string());
+        let children = args.children.to_owned().unwrap_or("".to_string());
+        let parents = args.parents.to_owned().unwrap_or("".to_string());
+        let mut exclude_ids = store.resolve_all(util::split_comma_exclude(ids.clone()))?;
+        if rchildren {
+            // prune the whole subtree below excluded items
+            for id in exclude_ids.clone() {
+                for d in store.descendants(&id) {
+                    if !exclude_ids.contains(&d) {
+                        exclude_ids.push(d);
+                    }
+                }
+            }
+        }
         Ok(Selector {
-            ids: util::split_comma_cleanup(ids.to_owned().unwrap_or("".to_string())),
-            children: util::split_comma_cleanup(children.to_owned().unwrap_or("".to_string())),
-            parents: util::split_comma_cleanup(parents.to_owned().unwrap_or("".to_string())),
-            tags: util::split_comma_tags(tags.to_owned().unwrap_or("".to_string())),
-            exclude_tags: util::split_comma_exclude_tags(tags.to_owned().unwrap_or("".to_string())),
-            before: util::parse_time(&before.to_owned().unwrap_or("".to_string()))?,
-            after: util::parse_time(&after.to_owned().unwrap_or("".to_string()))?,
-            started: *started,
-            stopped: *stopped,
-            rparents: *recursive > 1, // -rr only parents, -rrr both
-            rchildren: *recursive == 1 || *recursive > 2, // -r only children, -rrr both
-            or: *or,
+            ids: store.resolve_all(util::split_comma_include(ids))?,
+            children: store.resolve_all(util::split_comma_include(children.clone()))?,
+            parents: store.resolve_all(util::split_comma_include(parents.clone()))?,
+            tags: util::split_comma_include(args.tags.to_owned().unwrap_or("".to_string())),
+            exclude_tags: util::split_comma_exclude(args.tags.to_owned().unwrap_or("".to_string())),
+            exclude_ids,
+            exclude_children: store.resolve_all(util::split_comma_exclude(children))?,
+            exclude_parents: store.resolve_all(util::split_comma_exclude(parents))?,
+            before: util::parse_time_span(&args.before.to_owned().unwrap_or("".to_string()))?
+                .map(|(_, end)| end),
+            after: util::parse_time_span(&args.after.to_owned().unwrap_or("".to_string()))?
+                .map(|(start, _)| start),
+            modified_since: util::parse_time_span(
+                &args.modified_since.to_owned().unwrap_or("".to_string()),
+            )?
+            .map(|(start, _)| start),
+            worked_between: match (&args.worked_since, &args.worked_between) {
+                (Some(_), Some(_)) => {
+                    return Err("Use either --worked-since or --worked-between.".into())
+                }
+                (Some(t), None) => util::parse_time_span(t)?.map(|(start, _)| (start, i64::MAX)),
+                (None, Some(t)) => util::parse_time_span(t)?,
+                (None, None) => None,
+            },
+            spent_more: match &args.spent_more {
+                Some(t) => Some(util::parse_duration(t)?),
+                None => None,
+            },
+            spent_less: match &args.spent_less {
+                Some(t) => Some(util::parse_duration(t)?),
+                None => None,
+            },
+            spent_in: match &args.spent_in {
+                Some(_) if args.spent_more.is_none() && args.spent_less.is_none() => {
+                    return Err("--spent-in needs --spent-more or --spent-less.".into())
+                }
+                Some(t) => util::parse_time_span(t)?.unwrap_or((i64::MIN, i64::MAX)),
+                None => (i64::MIN, i64::MAX),
+            },
+            started: args.started,
+            stopped: args.stopped,
+            grep: match &args.grep {
+                Some(p) if args.regex => Some(Regex::new(p)?),
+                Some(p) => Some(Regex::new(&format!("(?i){}", regex::escape(p)))?),
+                None => None,
+            },
+            query: match &args.query {
+                Some(q) => Some(Query::parse(q)?),
+                None => None,
+            },
+            rparents: args.recursive > 1, // -rr only parents, -rrr both
+            rchildren,
+            // --depth counts the levels below the selected items
+            max_depth: args.depth.map(|d| d + 1).unwrap_or(MAX_DEPTH),
+            sort: Sort {
+                key: args.sort,
+                reverse: args.reverse,
+            },
+            limit: if args.first { Some(1) } else { args.limit },
+            offset: args.offset,
+            last: args.last,
+            or: args.or,
         })
     }
 
-    /// check if selector is empty, except exclude_tags
+    /// check if selector is empty, except the exclusions
     pub fn is_empty(&self) -> bool {
         return self.ids.is_empty()
             && self.children.is_empty()
@@ -56,39 +289,65 @@ impl Selector {
             && self.tags.is_empty()
             && self.before.is_none()
             && self.after.is_none()
+            && self.modified_since.is_none()
+            && self.worked_between.is_none()
+            && self.spent_more.is_none()
+            && self.spent_less.is_none()
             && !self.started
-            && !self.stopped;
+            && !self.stopped
+            && self.grep.is_none()
+            && self.query.is_none();
+    }
+
+    /// check if ids, parents, children or tags are excluded
+    pub fn has_exclusions(&self) -> bool {
+        !self.exclude_ids.is_empty()
+            || !self.exclude_children.is_empty()
+            || !self.exclude_parents.is_empty()
+            || !self.exclude_tags.is_empty()
     }
 
-    /// checks if item should be excluded
+    /// checks if item should be kept, false if it is excluded
     pub fn exclude(&self, key: &String, store: &Store) -> bool {
-        let item = store.get_item(key).unwrap();
-        !util::contains_element(item.tags(), &self.exclude_tags)
+        let Some(item) = store.get_item(key) else {
+            return false;
+        };
+        !self.exclude_tags.iter().any(|t| item.has_tag(t))
+            && !self.exclude_ids.contains(key)
+            && !util::contains_element(item.children(), &self.exclude_children)
+            && !util::contains_element(item.parents(), &self.exclude_parents)
     }
 
+    /// selected ids in sorted order, limited by offset, limit and last
+    /// recursively found ids are appended
     pub fn get(&self, store: &Store, recursive: bool) -> Vec<String> {
-        let mut r;
-        if self.or {
-            r = self.get_or(store);
+        let r = if self.or {
+            self.get_or(store)
         } else {
-            r = self.get_and(store);
-        }
+            self.get_and(store)
+        };
+        let r = r
+            .into_iter()
+            .filter(|key| self.exclude(key, store))
+            .collect::<Vec<String>>();
+        let mut r = self.window(store.sorted(&r, &self.sort));
         // the ItemView is not used, only the path to collect recursivly ids
         if recursive {
-            let mut path1 = vec![];
-            let mut path2 = vec![];
+            // excluded ids are marked as visited to prune the recursion there
+            let mut path1 = self.exclude_ids.clone();
+            let mut path2 = self.exclude_ids.clone();
             for id in &r {
                 if self.rparents {
                     let items = vec![id.to_owned()];
-                    store.recursive_execute(&items, &mut path1, 0, MAX_DEPTH, true);
+                    store.recursive_execute(&items, &mut path1, 0, self.max_depth, true, None);
                 }
                 if self.rchildren {
                     let items = vec![id.to_owned()];
-                    store.recursive_execute(&items, &mut path2, 0, MAX_DEPTH, false);
+                    store.recursive_execute(&items, &mut path2, 0, self.max_depth, false, None);
                 }
             }
             for i in path1.iter().chain(path2.iter()) {
-                if !r.contains(&i) {
+                if !r.contains(i) && !self.exclude_ids.contains(i) {
                     r.push(i.to_string());
                 }
             }
@@ -99,8 +358,26 @@ impl Selector {
             .collect::<Vec<String>>()
     }
 
+    /// tracked time of the item in the span of spent_in
+    fn spent(&self, item: &Item) -> i64 {
+        item.spent_between(self.spent_in.0, self.spent_in.1)
+    }
+
+    fn window(&self, mut ids: Vec<String>) -> Vec<String> {
+        if self.last {
+            return ids.pop().into_iter().collect();
+        }
+        let ids = ids.into_iter().skip(self.offset);
+        match self.limit {
+            Some(limit) => ids.take(limit).collect(),
+            None => ids.collect(),
+        }
+    }
+
     fn get_or(&self, store: &Store) -> Vec<String> {
-        let keys = store.get().keys().cloned().collect::<Vec<String>>();
+        let mut keys = store.get().keys().cloned().collect::<Vec<String>>();
+        // the store is a HashMap, sort for the same order on every run
+        keys.sort();
         let keys = keys
             .iter()
             .filter(|key| {
@@ -109,11 +386,23 @@ impl Selector {
                     || self.ids.contains(key)
                     || util::contains_element(item.children(), &self.children)
                     || util::contains_element(item.parents(), &self.parents)
-                    || util::contains_element(item.tags(), &self.tags)
+                    || self.tags.iter().any(|t| item.has_tag(t))
                     || self.before.is_some() && item.timestamp() < self.before.unwrap()
-                    || self.after.is_some() && item.timestamp() > self.after.unwrap()
+                    || self.after.is_some() && item.timestamp() >= self.after.unwrap()
+                    || self.modified_since.is_some()
+                        && item.last_modified() >= self.modified_since.unwrap()
+                    || self
+                        .worked_between
+                        .is_some_and(|(start, end)| item.worked_between(start, end))
+                    || self.spent_more.is_some_and(|t| self.spent(item) > t)
+                    || self.spent_less.is_some_and(|t| self.spent(item) < t)
                     || item.is_started() && self.started
                     || item.is_stopped() && self.stopped
+                    || self
+                        .grep
+                        .as_ref()
+                        .is_some_and(|re| re.is_match(item.content()))
+                    || self.query.as_ref().is_some_and(|q| q.matches(item))
             })
             .map(|s| s.to_owned())
             .collect::<Vec<String>>();
@@ -121,7 +410,9 @@ impl Selector {
     }
 
     fn get_and(&self, store: &Store) -> Vec<String> {
-        let keys = store.get().keys().cloned().collect::<Vec<String>>();
+        let mut keys = store.get().keys().cloned().collect::<Vec<String>>();
+        // the store is a HashMap, sort for the same order on every run
+        keys.sort();
         let keys = keys
             .iter()
             .filter(|key| {
@@ -130,11 +421,21 @@ impl Selector {
                     && (self.children.is_empty()
                         || util::is_subset(&self.children, item.children()))
                     && (self.parents.is_empty() || util::is_subset(&self.parents, item.parents()))
-                    && (self.tags.is_empty() || util::is_subset(&self.tags, item.tags()))
+                    && self.tags.iter().all(|t| item.has_tag(t))
                     && (self.before.is_none() || item.timestamp() < self.before.unwrap())
-                    && (self.after.is_none() || item.timestamp() > self.after.unwrap())
+                    && (self.after.is_none() || item.timestamp() >= self.after.unwrap())
+                    && (self.modified_since.is_none()
+                        || item.last_modified() >= self.modified_since.unwrap())
+                    && (self.worked_between.is_none() || {
+                        let (start, end) = self.worked_between.unwrap();
+                        item.worked_between(start, end)
+                    })
+                    && (self.spent_more.is_none() || self.spent(item) > self.spent_more.unwrap())
+                    && (self.spent_less.is_none() || self.spent(item) < self.spent_less.unwrap())
                     && (!self.started || item.is_started())
                     && (!self.stopped || item.is_stopped())
+                    && (self.grep.is_none() || self.grep.as_ref().unwrap().is_match(item.content()))
+                    && (self.query.is_none() || self.query.as_ref().unwrap().matches(item))
             })
             .map(|s| s.to_owned())
             .collect::<Vec<_>>();
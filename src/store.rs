@@ -2,15 +2,24 @@ pub use self::inner::RecState;
 pub use self::inner::Store;
 pub use self::inner::MAX_DEPTH;
 
+#[cfg(test)]
+mod tests;
+
 pub mod inner {
     pub const MAX_DEPTH: usize = 10; /*std::usize::MAX*/
     const MD_DELIMITER: &str = "\n---\n---\n---\n";
+    /// separates the segments of an item path
+    const PATH_DELIMITER: char = '/';
 
     use std::error::Error;
-    use std::{collections::HashMap, str::FromStr};
+    use std::{
+        cmp::Ordering,
+        collections::{BTreeMap, HashMap, HashSet},
+        str::FromStr,
+    };
 
-    use crate::item::Item;
     use crate::error::ParseError;
+    use crate::item::{Item, Rollup, Sort};
     use serde::{Deserialize, Serialize};
 
     #[derive(Copy, Clone)]
@@ -27,7 +36,7 @@ pub mod inner {
         pub has_children: bool,
     }
 
-    #[derive(Serialize, Deserialize)]
+    #[derive(Serialize, Deserialize, Clone, Default)]
     pub struct Store {
         items: HashMap<String, Item>,
     }
@@ -135,7 +144,7 @@ pub mod inner {
                     .cmp(&self.items.get(b).unwrap().timestamp())
             });
             for (i, id) in keys.iter().enumerate() {
-                serialized += &self.get_item(id).unwrap().print_long(true);
+                serialized += &self.get_item(id).unwrap().print_long(true, None);
                 if i + 1 < self.items.len() {
                     // delimiter of entries
                     serialized += MD_DELIMITER;
@@ -176,6 +185,94 @@ pub mod inner {
             Ok(())
         }
 
+        /// merges the item drop into keep and removes drop.
+        /// All relations of drop are moved to keep
+        pub fn merge(&mut self, keep: &str, drop: &str) -> Result<(), Box<dyn Error>> {
+            if keep == drop {
+                return Err("Can not merge an item with itself.".into());
+            }
+            let dropped = self.get_item(drop).ok_or("Could not found id")?.clone();
+            self.get_item_mut(keep)
+                .ok_or("Could not found id")?
+                .absorb(&dropped)?;
+            self.remove(drop)?;
+            for id in dropped.parents() {
+                let kept = self.get_item(keep).unwrap().clone();
+                if id == keep || kept.parents().contains(id) {
+                    continue;
+                }
+                if let Some(parent) = self.get_item_mut(id) {
+                    parent.add_child(&kept);
+                    let parent = parent.clone();
+                    self.get_item_mut(keep).unwrap().add_parent(&parent);
+                }
+            }
+            for id in dropped.children() {
+                let kept = self.get_item(keep).unwrap().clone();
+                if id == keep || kept.children().contains(id) {
+                    continue;
+                }
+                if let Some(child) = self.get_item_mut(id) {
+                    child.add_parent(&kept);
+                    let child = child.clone();
+                    self.get_item_mut(keep).unwrap().add_child(&child);
+                }
+            }
+            Ok(())
+        }
+
+        /// resolves a path like cake/c3e/70f to the id of its last item.
+        /// Every segment after the first one is a child id or a unique,
+        /// case insensitive prefix of a child title.
+        /// Single segments are returned unchanged, they may name new items
+        pub fn resolve(&self, path: &str) -> Result<String, Box<dyn Error>> {
+            if self.items.contains_key(path) || !path.contains(PATH_DELIMITER) {
+                return Ok(path.to_string());
+            }
+            let mut segments = path.split(PATH_DELIMITER).filter(|s| !s.is_empty());
+            let mut current = segments.next().ok_or("Empty path")?.to_string();
+            if !self.items.contains_key(&current) {
+                return Err(format!("Could not found id '{}' of path '{}'", current, path).into());
+            }
+            for segment in segments {
+                let children = self.items.get(&current).unwrap().children();
+                if children.contains(&segment.to_string()) {
+                    current = segment.to_string();
+                    continue;
+                }
+                let prefix = segment.to_lowercase();
+                let matches = children
+                    .iter()
+                    .filter(|c| match self.items.get(*c) {
+                        Some(item) => item.title().to_lowercase().starts_with(&prefix),
+                        None => false,
+                    })
+                    .collect::<Vec<&String>>();
+                match matches.len() {
+                    0 => {
+                        return Err(
+                            format!("No child of '{}' matches '{}'", current, segment).into()
+                        )
+                    }
+                    1 => current = matches[0].to_string(),
+                    _ => {
+                        return Err(format!(
+                            "'{}' is ambiguous below '{}', it matches {}",
+                            segment,
+                            current,
+                            crate::util::vec_to_str(&matches)
+                        )
+                        .into())
+                    }
+                }
+            }
+            Ok(current)
+        }
+
+        pub fn resolve_all(&self, paths: Vec<String>) -> Result<Vec<String>, Box<dyn Error>> {
+            paths.iter().map(|p| self.resolve(p)).collect()
+        }
+
         pub fn get_item(&self, id: &str) -> Option<&Item> {
             return self.items.get(id);
         }
@@ -188,9 +285,127 @@ pub mod inner {
             return &self.items;
         }
 
+        /// number of items and last modification for every tag
+        pub fn tag_usage(&self) -> BTreeMap<String, (usize, i64)> {
+            let mut res: BTreeMap<String, (usize, i64)> = BTreeMap::new();
+            for item in self.items.values() {
+                for tag in item.tags() {
+                    let usage = res.entry(tag.to_owned()).or_insert((0, 0));
+                    usage.0 += 1;
+                    usage.1 = usage.1.max(item.last_modified());
+                }
+            }
+            res
+        }
+
+        /// renames the tag and its namespace in all items, removes them if new is None.
+        /// Returns the number of changed items
+        pub fn rename_tag(&mut self, old: &str, new: Option<&str>) -> usize {
+            self.items
+                .values_mut()
+                .filter_map(|item| item.rename_tag(old, new).then_some(()))
+                .count()
+        }
+
+        /// collects the ids of all items below the given one,
+        /// items reachable on multiple paths are contained only once
+        pub fn descendants(&self, id: &str) -> HashSet<String> {
+            let mut res = HashSet::new();
+            let mut stack = vec![id.to_string()];
+            while let Some(s) = stack.pop() {
+                if let Some(item) = self.items.get(&s) {
+                    for child in item.children() {
+                        if res.insert(child.to_owned()) {
+                            stack.push(child.to_owned());
+                        }
+                    }
+                }
+            }
+            // the item itself is part of a cycle
+            res.remove(id);
+            res
+        }
+
+        /// summary of time and progress over all descendants, None for items without children
+        pub fn rollup(&self, id: &str) -> Option<Rollup> {
+            let item = self.items.get(id)?;
+            if item.children().is_empty() {
+                return None;
+            }
+            let mut rollup = Rollup {
+                spent: item.spent(),
+                ..Default::default()
+            };
+            for d in self.descendants(id) {
+                if let Some(i) = self.items.get(&d) {
+                    rollup.spent += i.spent();
+                    rollup.descendants += 1;
+                    if i.is_done() {
+                        rollup.done += 1;
+                    } else {
+                        rollup.open += 1;
+                    }
+                    if i.is_started() {
+                        rollup.started += 1;
+                    }
+                }
+            }
+            Some(rollup)
+        }
+
+        /// all paths from a root item down to the given one, following the parents.
+        /// Every path ends with the id itself, multiple parents lead to multiple paths
+        pub fn ancestor_paths(&self, id: &str) -> Vec<Vec<String>> {
+            let mut res = vec![];
+            self.collect_ancestor_paths(&mut vec![id.to_string()], &mut res);
+            res
+        }
+
+        fn collect_ancestor_paths(&self, path: &mut Vec<String>, res: &mut Vec<Vec<String>>) {
+            let mut is_root = true;
+            if let Some(item) = self.items.get(&path[0]) {
+                for parent in item.parents() {
+                    // skip cycles and dangling relations
+                    if path.contains(parent) || !self.items.contains_key(parent) {
+                        continue;
+                    }
+                    is_root = false;
+                    path.insert(0, parent.to_owned());
+                    self.collect_ancestor_paths(path, res);
+                    path.remove(0);
+                }
+            }
+            if is_root {
+                res.push(path.clone());
+            }
+        }
+
+        /// sorts ids of existing items, unknown ids keep their place at the end
+        pub fn sorted(&self, ids: &[String], sort: &Sort) -> Vec<String> {
+            self.sorted_by(ids, |a, b| sort.cmp(a, b))
+        }
+
+        /// parents and children with equal keys keep their order, e.g. the sections of split
+        fn sorted_relations(&self, ids: &[String], sort: &Sort) -> Vec<String> {
+            self.sorted_by(ids, |a, b| sort.cmp_key(a, b))
+        }
+
+        fn sorted_by<F>(&self, ids: &[String], cmp: F) -> Vec<String>
+        where
+            F: Fn(&Item, &Item) -> Ordering,
+        {
+            let mut ids = ids.to_vec();
+            ids.sort_by(|a, b| match (self.items.get(a), self.items.get(b)) {
+                (Some(a), Some(b)) => cmp(a, b),
+                (a, b) => b.is_some().cmp(&a.is_some()),
+            });
+            ids
+        }
+
         /// generates an ItemView for every element in items, executing children recursively
         /// ids are collected in path
         /// @up to recursivly go through parents
+        /// @sort orders the parents or children on every level, items keep their order
         pub fn recursive_execute<'a>(
             &'a self,
             items: &Vec<String>,
@@ -198,6 +413,7 @@ pub mod inner {
             depth: usize,
             max_depth: usize,
             up: bool, // recursive for parents
+            sort: Option<&Sort>,
         ) -> Vec<ItemView<'a>> {
             let mut res: Vec<ItemView<'a>> = vec![];
             if depth == max_depth {
@@ -211,11 +427,15 @@ pub mod inner {
                         let mut res2 = vec![];
                         if up {
                             res1 = self.recursive_execute(
-                                &_item.parents(),
+                                &match sort {
+                                    Some(sort) => self.sorted_relations(_item.parents(), sort),
+                                    None => _item.parents().to_owned(),
+                                },
                                 path,
                                 depth + 1,
                                 max_depth,
                                 up,
+                                sort,
                             );
                         }
                         res.append(&mut res1);
@@ -227,11 +447,15 @@ pub mod inner {
                         });
                         if !up {
                             res2 = self.recursive_execute(
-                                &_item.children(),
+                                &match sort {
+                                    Some(sort) => self.sorted_relations(_item.children(), sort),
+                                    None => _item.children().to_owned(),
+                                },
                                 path,
                                 depth + 1,
                                 max_depth,
                                 up,
+                                sort,
                             );
                         }
                         res.append(&mut res2);
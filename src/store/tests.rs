@@ -0,0 +1,119 @@
+use crate::item::{Item, Sort, SortKey};
+use crate::store::Store;
+
+/// store of items with their parents, parents have to be listed before their children
+fn store(items: &[(&str, &[&str])]) -> Store {
+    let mut store = Store::default();
+    for (id, parents) in items {
+        let parents = parents.iter().map(|p| p.to_string()).collect();
+        let mut item = Item::new(id.to_string(), vec![], parents, vec![]);
+        item.set_content(format!("# {}", id));
+        store.add(item).unwrap();
+    }
+    store
+}
+
+fn relations(store: &Store, id: &str) -> (Vec<String>, Vec<String>) {
+    let item = store.get_item(id).unwrap();
+    let mut parents = item.parents().to_owned();
+    let mut children = item.children().to_owned();
+    parents.sort();
+    children.sort();
+    (parents, children)
+}
+
+#[test]
+fn test_merge() {
+    // drop is a child of keep, its children move up to keep
+    let mut s = store(&[("a", &[]), ("b", &["a"]), ("c", &["b"])]);
+    s.merge("a", "b").unwrap();
+    assert!(s.get_item("b").is_none());
+    assert_eq!(relations(&s, "a"), (vec![], vec!["c".to_string()]));
+    assert_eq!(relations(&s, "c"), (vec!["a".to_string()], vec![]));
+
+    // drop is the parent of keep, keep takes its place below the root
+    let mut s = store(&[("r", &[]), ("a", &["r"]), ("b", &["a"])]);
+    s.merge("b", "a").unwrap();
+    assert_eq!(relations(&s, "b"), (vec!["r".to_string()], vec![]));
+    assert_eq!(relations(&s, "r"), (vec![], vec!["b".to_string()]));
+
+    // shared parents and children are kept once
+    let mut s = store(&[
+        ("p", &[]),
+        ("q", &[]),
+        ("x", &["p"]),
+        ("y", &["p", "q"]),
+        ("c", &["x", "y"]),
+    ]);
+    s.merge("x", "y").unwrap();
+    assert_eq!(
+        relations(&s, "x"),
+        (
+            vec!["p".to_string(), "q".to_string()],
+            vec!["c".to_string()]
+        )
+    );
+    assert_eq!(s.get_item("p").unwrap().children(), &vec!["x".to_string()]);
+    assert_eq!(s.get_item("q").unwrap().children(), &vec!["x".to_string()]);
+    assert_eq!(s.get_item("c").unwrap().parents(), &vec!["x".to_string()]);
+
+    assert!(s.merge("x", "x").is_err());
+    assert!(s.merge("x", "zzz").is_err());
+}
+
+#[test]
+fn test_resolve() {
+    let mut s = store(&[
+        ("cake", &[]),
+        ("c3e", &["cake"]),
+        ("70f", &["cake"]),
+        ("71a", &["cake"]),
+    ]);
+    s.get_item_mut("c3e")
+        .unwrap()
+        .set_content("# Config file".to_string());
+    s.get_item_mut("70f")
+        .unwrap()
+        .set_content("# Run cmd".to_string());
+    s.get_item_mut("71a")
+        .unwrap()
+        .set_content("# Run tests".to_string());
+
+    // single segments are kept, they may be new ids
+    assert_eq!(s.resolve("c3e").unwrap(), "c3e");
+    assert_eq!(s.resolve("new").unwrap(), "new");
+    // child ids and case insensitive title prefixes
+    assert_eq!(s.resolve("cake/c3e").unwrap(), "c3e");
+    assert_eq!(s.resolve("cake/config").unwrap(), "c3e");
+    assert_eq!(s.resolve("cake/RUN C").unwrap(), "70f");
+    assert_eq!(
+        s.resolve("cake/run").unwrap_err().to_string(),
+        "'run' is ambiguous below 'cake', it matches 70f, 71a"
+    );
+    assert_eq!(
+        s.resolve("cake/build").unwrap_err().to_string(),
+        "No child of 'cake' matches 'build'"
+    );
+    assert_eq!(
+        s.resolve("zzz/c3e").unwrap_err().to_string(),
+        "Could not found id 'zzz' of path 'zzz/c3e'"
+    );
+}
+
+#[test]
+fn test_recursive_execute_order() {
+    // created in the same second, the children keep their order on equal keys
+    let s = store(&[("p", &[]), ("z", &["p"]), ("a", &["p"])]);
+    let ids = |sort: &Sort| {
+        s.recursive_execute(&vec!["p".to_string()], &mut vec![], 0, 2, false, Some(sort))
+            .iter()
+            .map(|view| view.item.id().to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(ids(&Sort::default()), vec!["p", "z", "a"]);
+    let by_id = Sort {
+        key: SortKey::Id,
+        reverse: false,
+    };
+    assert_eq!(ids(&by_id), vec!["p", "a", "z"]);
+}
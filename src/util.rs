@@ -1,7 +1,10 @@
 use crate::error;
-use chrono::{DateTime, Local, TimeZone};
+use chrono::{
+    DateTime, Datelike, Local, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Weekday,
+};
 use directories::{BaseDirs, ProjectDirs};
 use nanoid::nanoid;
+use regex::Regex;
 use std::{
     env::temp_dir,
     error::Error,
@@ -10,6 +13,7 @@ use std::{
     path::PathBuf,
     process::Command,
 };
+use termimad::crossterm::style::Stylize;
 
 #[cfg(test)]
 mod tests {
@@ -17,22 +21,229 @@ mod tests {
 
     //#[should_panic]
     #[test]
-    fn test_parse_time() {
-        assert_eq!(parse_time("3s").unwrap().unwrap(), timestamp() - 3);
-        assert_eq!(parse_time("1h").unwrap().unwrap(), timestamp() - 60 * 60);
-        assert_eq!(parse_time("30m").unwrap().unwrap(), timestamp() - 30 * 60);
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("3s").unwrap(), 3);
+        assert_eq!(parse_duration("1h").unwrap(), 60 * 60);
+        assert_eq!(parse_duration("30m").unwrap(), 30 * 60);
+        assert_eq!(
+            parse_duration("30m1s4w").unwrap(),
+            30 * 60 + 1 + 4 * 7 * 24 * 60 * 60
+        );
+        assert_eq!(
+            parse_duration("1y1d").unwrap(),
+            365 * 24 * 60 * 60 + 24 * 60 * 60
+        );
+        assert!(parse_duration("100sc1hwac3h1sinn").is_err());
+        assert!(parse_duration("1T").is_err());
+        assert!(parse_duration("0.5Y").is_err());
+        assert!(parse_duration("1h30").is_err());
+    }
+
+    #[test]
+    fn test_parse_time_span() {
+        let at = |y, m, d, h, min| Local.with_ymd_and_hms(y, m, d, h, min, 0).unwrap();
+        // a wednesday
+        let now = at(2025, 3, 12, 15, 30);
+        let span = |t| parse_time_span_at(t, now).unwrap().unwrap();
+        let ts = |y, m, d| at(y, m, d, 0, 0).timestamp();
+
+        assert_eq!(parse_time_span_at("", now).unwrap(), None);
+        assert_eq!(span("1h").0, now.timestamp() - 60 * 60);
+        assert_eq!(span("now"), (now.timestamp(), now.timestamp()));
+        assert_eq!(span("today"), (ts(2025, 3, 12), ts(2025, 3, 13)));
+        assert_eq!(span("Yesterday"), (ts(2025, 3, 11), ts(2025, 3, 12)));
+        assert_eq!(span("last monday"), (ts(2025, 3, 10), ts(2025, 3, 11)));
+        assert_eq!(span("last wednesday"), (ts(2025, 3, 5), ts(2025, 3, 6)));
+        assert_eq!(span("wednesday"), (ts(2025, 3, 12), ts(2025, 3, 13)));
+        assert_eq!(span("last week"), (ts(2025, 3, 3), ts(2025, 3, 10)));
+        assert_eq!(span("this month"), (ts(2025, 3, 1), ts(2025, 4, 1)));
+        assert_eq!(span("2025-02-28"), (ts(2025, 2, 28), ts(2025, 3, 1)));
+        assert_eq!(span("2025-02"), (ts(2025, 2, 1), ts(2025, 3, 1)));
+        assert_eq!(span("2024"), (ts(2024, 1, 1), ts(2025, 1, 1)));
+        assert_eq!(span("2025-01..2025-03"), (ts(2025, 1, 1), ts(2025, 4, 1)));
+        let t = at(2025, 3, 11, 14, 0).timestamp();
+        assert_eq!(span("yesterday 14:00"), (t, t));
+        assert_eq!(span("2025-03-11T14:00"), (t, t));
+        assert_eq!(span("2025-03-11 14:00:00"), (t, t));
+        assert!(parse_time_span_at("2025-03..2025-01", now).is_err());
+        assert!(parse_time_span_at("someday", now).is_err());
+        assert!(parse_time_span_at("2025-13", now).is_err());
+    }
+
+    #[test]
+    fn test_parse_week() {
+        let at = |y, m, d, h| Local.with_ymd_and_hms(y, m, d, h, 0, 0).unwrap();
+        let now = at(2025, 3, 12, 15);
+        let week = (
+            at(2025, 3, 10, 0).timestamp(),
+            at(2025, 3, 17, 0).timestamp(),
+        );
+        assert_eq!(parse_week_at("this week", now).unwrap(), week);
+        assert_eq!(parse_week_at("2025-03-16", now).unwrap(), week);
+        assert_eq!(parse_week_at("tomorrow", now).unwrap(), week);
+        assert_eq!(
+            parse_week_at("last week", now).unwrap(),
+            (at(2025, 3, 3, 0).timestamp(), week.0)
+        );
+    }
+
+    #[test]
+    fn test_split_days() {
+        let at = |y, m, d, h| {
+            Local
+                .with_ymd_and_hms(y, m, d, h, 0, 0)
+                .unwrap()
+                .timestamp()
+        };
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(
+            split_days(at(2025, 3, 10, 22), at(2025, 3, 11, 2)).unwrap(),
+            vec![
+                (date(2025, 3, 10), at(2025, 3, 10, 22), at(2025, 3, 11, 0)),
+                (date(2025, 3, 11), at(2025, 3, 11, 0), at(2025, 3, 11, 2))
+            ]
+        );
+        // days end at local midnight, a switch to summer time shortens the day
+        let days = split_days(at(2025, 3, 30, 0), at(2025, 4, 1, 0)).unwrap();
+        assert_eq!(days.len(), 2);
         assert_eq!(
-            parse_time("30m1s4w").unwrap().unwrap(),
-            timestamp() - (30 * 60 + 1 + 4 * 7 * 24 * 60 * 60)
+            days[0].2 - days[0].1,
+            at(2025, 3, 31, 0) - at(2025, 3, 30, 0)
         );
         assert_eq!(
-            parse_time("1y1d").unwrap().unwrap(),
-            timestamp() - (365 * 24 * 60 * 60 + 24 * 60 * 60)
+            days[1].2 - days[1].1,
+            at(2025, 4, 1, 0) - at(2025, 3, 31, 0)
         );
-        assert!(parse_time("100sc1hwac3h1sinn").is_err());
-        assert!(parse_time("1T").is_err());
-        assert!(parse_time("0.5Y").is_err());
+        assert!(split_days(at(2025, 3, 10, 0), at(2025, 3, 10, 0))
+            .unwrap()
+            .is_empty());
     }
+
+    #[test]
+    fn test_split_comma() {
+        let s = "c3e, ~cake/70f,,~|x ,a|b".to_string();
+        assert_eq!(split_comma_include(s.clone()), vec!["c3e", "ab"]);
+        assert_eq!(split_comma_exclude(s), vec!["cake/70f", "x"]);
+    }
+
+    #[test]
+    fn test_split_args() {
+        let args = split_args("-t work  -q \"tag:a and started\" -p ''").unwrap();
+        assert_eq!(
+            args,
+            vec!["-t", "work", "-q", "tag:a and started", "-p", ""]
+        );
+        assert_eq!(join_args(&args), "-t work -q \"tag:a and started\" -p \"\"");
+        assert_eq!(split_args(&join_args(&args)).unwrap(), args);
+        assert!(split_args("-q \"open").is_err());
+    }
+
+    #[test]
+    fn test_group_tags() {
+        let tags = ["a/b", "d", "a/c/e", "f/g"].map(String::from);
+        assert_eq!(group_tags(&tags, '/'), vec!["a/{b, c/e}", "d", "f/g"]);
+        // the namespace itself stays a separate tag
+        let tags = ["a", "a/b"].map(String::from);
+        assert_eq!(group_tags(&tags, '/'), vec!["a", "a/b"]);
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        assert_eq!(diff_lines("a\nb\nc", "a\nx\nc"), vec!["- b", "+ x"]);
+        // moved and duplicated lines are changes too
+        assert_eq!(diff_lines("a\nb", "b\na"), vec!["- a", "+ a"]);
+        assert_eq!(diff_lines("a\nb", "a\nb\na"), vec!["+ a"]);
+        assert!(diff_lines("a\nb", "a\nb").is_empty());
+    }
+
+    #[test]
+    fn test_split_sections() {
+        let (intro, sections) = split_sections("# Title\nintro\n## a\ntext\n### sub\n## b\n");
+        assert_eq!(intro, "# Title\nintro\n");
+        assert_eq!(sections, vec!["# a\ntext\n### sub\n", "# b\n"]);
+
+        let (intro, sections) = split_sections("todo\n- one\n  more\n- two\noutro\n");
+        assert_eq!(intro, "todo\noutro\n");
+        assert_eq!(sections, vec!["one\nmore\n", "two\n"]);
+
+        let (intro, sections) = split_sections("# Title\n```\n# no heading\n```\n");
+        assert_eq!(intro, "# Title\n```\n# no heading\n```\n");
+        assert!(sections.is_empty());
+
+        let (intro, sections) =
+            split_sections("# Parent\n```\n# comment\n```\n## first\n## second\n");
+        assert_eq!(intro, "# Parent\n```\n# comment\n```\n");
+        assert_eq!(sections, vec!["# first\n", "# second\n"]);
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("skn", "the skin file").is_some());
+        assert!(fuzzy_score("SKIN", "the skin file").is_some());
+        assert!(fuzzy_score("xyz", "the skin file").is_none());
+        assert!(fuzzy_score("", "the skin file").is_none());
+        assert!(
+            fuzzy_score("skin", "the skin file").unwrap()
+                > fuzzy_score("skin", "sk is in").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_tree_prefixes() {
+        assert_eq!(
+            tree_prefixes(&[0, 1, 2, 1, 0, 1]),
+            vec!["", "├─ ", "│  └─ ", "└─ ", "", "└─ "]
+        );
+        assert_eq!(
+            tree_prefixes(&[0, 1, 1, 2]),
+            vec!["", "├─ ", "└─ ", "   └─ "]
+        );
+    }
+}
+
+/// splits a command line into arguments, double or single quotes group words with spaces
+pub fn split_args(s: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut args = vec![];
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+        let mut arg = String::new();
+        let mut quote = None;
+        let mut c = Some(c);
+        while let Some(ch) = c {
+            match quote {
+                Some(q) if ch == q => quote = None,
+                Some(_) => arg.push(ch),
+                None if ch == '"' || ch == '\'' => quote = Some(ch),
+                None if ch.is_whitespace() => break,
+                None => arg.push(ch),
+            }
+            c = chars.next();
+        }
+        if let Some(q) = quote {
+            return Err(format!("Unclosed {} in '{}'", q, s).into());
+        }
+        args.push(arg);
+    }
+    Ok(args)
+}
+
+/// joins arguments to a command line, arguments with spaces are quoted, see split_args
+pub fn join_args(args: &[String]) -> String {
+    args.iter()
+        .map(|a| {
+            if !a.is_empty() && !a.contains(char::is_whitespace) {
+                a.to_string()
+            } else if a.contains('"') {
+                format!("'{}'", a)
+            } else {
+                format!("\"{}\"", a)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
 pub fn generate_id() -> String {
@@ -43,12 +254,8 @@ pub fn generate_id() -> String {
     nanoid!(3, &alphabet)
 }
 
-/// parse e.g. 1y1w1d1h1m1s and subtracts it from now
-pub fn parse_time(t: &str) -> Result<Option<i64>, Box<dyn Error>> {
-    if t.is_empty() {
-        return Ok(None);
-    }
-
+/// parse e.g. 1y1w1d1h1m1s into seconds
+pub fn parse_duration(t: &str) -> Result<i64, Box<dyn Error>> {
     const NUMBERS: [char; 10] = ['0', '1', '2', '3', '4', '5', '6', '7', '8', '9'];
     const UNIT: [char; 6] = ['y', 'w', 'd', 'h', 'm', 's'];
     let mut values: [i64; 6] = [0, 0, 0, 0, 0, 0];
@@ -67,20 +274,181 @@ pub fn parse_time(t: &str) -> Result<Option<i64>, Box<dyn Error>> {
             return Err("Wrong time format".into());
         }
     }
+    if last_pos < t.len() {
+        return Err("Missing time unit".into());
+    }
     let t = 365 * 24 * 60 * 60 * values[0] // years
         + 7 * 24 * 60 * 60 * values[1] // week
         + 24 * 60 * 60 * values[2] // days
         + 60 * 60 * values[3] // hours
         + 60 * values[4] // minutes
         + values[5]; // seconds
-    let now = timestamp();
-    Ok(Some(now - t))
+    Ok(t)
+}
+
+/// parses a point or a span of time into its start and exclusive end timestamp.
+/// Accepted are durations like 1w2d (ago), now, today, yesterday, tomorrow,
+/// this or last week and month, weekdays like monday or last monday,
+/// dates like 2025, 2025-03 or 2025-03-01,
+/// a day with time like 2025-03-01 14:00 or yesterday 14:00
+/// and ranges like 2025-01..2025-03. Dates are in the local time zone
+pub fn parse_time_span(t: &str) -> Result<Option<(i64, i64)>, Box<dyn Error>> {
+    parse_time_span_at(t, Local::now())
+}
+
+fn parse_time_span_at(t: &str, now: DateTime<Local>) -> Result<Option<(i64, i64)>, Box<dyn Error>> {
+    let t = t.trim().to_lowercase();
+    if t.is_empty() {
+        return Ok(None);
+    }
+    if let Some((from, to)) = t.split_once("..") {
+        let from = parse_time_span_at(from, now)?.ok_or("Missing start of the range")?;
+        let to = parse_time_span_at(to, now)?.ok_or("Missing end of the range")?;
+        if from.0 > to.1 {
+            return Err("The range ends before it starts".into());
+        }
+        return Ok(Some((from.0, to.1)));
+    }
+    if t == "now" {
+        return Ok(Some((now.timestamp(), now.timestamp())));
+    }
+    // a day with a time of day is a point in time
+    if let Some((day, time)) = t.rsplit_once([' ', 't']) {
+        if let Ok(time) = NaiveTime::parse_from_str(time, "%H:%M:%S")
+            .or_else(|_| NaiveTime::parse_from_str(time, "%H:%M"))
+        {
+            let (start, _) = parse_days(day.trim(), now.date_naive())?;
+            let timestamp = local_timestamp(start.and_time(time))?;
+            return Ok(Some((timestamp, timestamp)));
+        }
+    }
+    // durations contain only numbers and units, a plain number is a year
+    let is_year = t.len() == 4 && t.chars().all(|c| c.is_ascii_digit());
+    if !is_year
+        && t.chars().all(|c| c.is_ascii_alphanumeric())
+        && t.chars().any(|c| c.is_ascii_digit())
+    {
+        let timestamp = now.timestamp() - parse_duration(&t)?;
+        return Ok(Some((timestamp, timestamp)));
+    }
+    let (start, end) = parse_days(&t, now.date_naive())?;
+    Ok(Some((
+        local_timestamp(start.and_hms_opt(0, 0, 0).unwrap())?,
+        local_timestamp(end.and_hms_opt(0, 0, 0).unwrap())?,
+    )))
+}
+
+/// parses a description of days relative to today into the first and the exclusive last day
+fn parse_days(t: &str, today: NaiveDate) -> Result<(NaiveDate, NaiveDate), Box<dyn Error>> {
+    let day = |d: NaiveDate| (d, d.succ_opt().unwrap());
+    let weekday_before = |weekday: Weekday, min_days: i64| {
+        let back = (today.weekday().num_days_from_monday() as i64
+            - weekday.num_days_from_monday() as i64
+            + 7)
+            % 7;
+        let back = if back < min_days { back + 7 } else { back };
+        day(today - chrono::Duration::days(back))
+    };
+    match t {
+        "today" => return Ok(day(today)),
+        "yesterday" => return Ok(day(today.pred_opt().unwrap())),
+        "tomorrow" => return Ok(day(today.succ_opt().unwrap())),
+        "this week" | "last week" => {
+            let monday = weekday_before(Weekday::Mon, 0).0;
+            let monday = if t == "last week" {
+                monday - chrono::Duration::weeks(1)
+            } else {
+                monday
+            };
+            return Ok((monday, monday + chrono::Duration::weeks(1)));
+        }
+        "this month" | "last month" => {
+            let first = today.with_day(1).unwrap();
+            let first = if t == "last month" {
+                first.checked_sub_months(Months::new(1)).unwrap()
+            } else {
+                first
+            };
+            return Ok((first, first.checked_add_months(Months::new(1)).unwrap()));
+        }
+        _ => (),
+    }
+    if let Some(weekday) = t.strip_prefix("last ") {
+        if let Ok(weekday) = weekday.trim().parse::<Weekday>() {
+            return Ok(weekday_before(weekday, 1));
+        }
+    }
+    if let Ok(weekday) = t.parse::<Weekday>() {
+        return Ok(weekday_before(weekday, 0));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(t, "%Y-%m-%d") {
+        return Ok(day(date));
+    }
+    let first_of = |s: &str| NaiveDate::parse_from_str(&format!("{}-01", s), "%Y-%m-%d");
+    if t.len() == 7 {
+        if let Ok(date) = first_of(t) {
+            return Ok((date, date.checked_add_months(Months::new(1)).unwrap()));
+        }
+    }
+    if t.len() == 4 {
+        if let Ok(date) = first_of(&format!("{}-01", t)) {
+            return Ok((date, date.checked_add_months(Months::new(12)).unwrap()));
+        }
+    }
+    Err(format!("Unknown time '{}'", t).into())
+}
+
+fn local_timestamp(datetime: NaiveDateTime) -> Result<i64, Box<dyn Error>> {
+    Ok(Local
+        .from_local_datetime(&datetime)
+        .earliest()
+        .ok_or("Time does not exist in the local time zone")?
+        .timestamp())
+}
+
+/// the week from monday to sunday which contains the start of the time span, e.g. "last week",
+/// 2025-03-12 or yesterday
+pub fn parse_week(t: &str) -> Result<(i64, i64), Box<dyn Error>> {
+    parse_week_at(t, Local::now())
+}
+
+fn parse_week_at(t: &str, now: DateTime<Local>) -> Result<(i64, i64), Box<dyn Error>> {
+    let (start, _) = parse_time_span_at(t, now)?.ok_or("Missing week")?;
+    let day = Local.timestamp_opt(start, 0).unwrap().date_naive();
+    let monday = day - chrono::Duration::days(day.weekday().num_days_from_monday() as i64);
+    Ok((
+        local_timestamp(monday.and_hms_opt(0, 0, 0).unwrap())?,
+        local_timestamp(
+            (monday + chrono::Duration::weeks(1))
+                .and_hms_opt(0, 0, 0)
+                .unwrap(),
+        )?,
+    ))
+}
+
+/// a day with the start and end of the time spent on it
+pub type DaySpan = (NaiveDate, i64, i64);
+
+/// splits the time span at local midnight into the days it overlaps
+/// with the part of the span on every day
+pub fn split_days(start: i64, end: i64) -> Result<Vec<DaySpan>, Box<dyn Error>> {
+    let mut res = vec![];
+    let mut day = Local.timestamp_opt(start, 0).unwrap().date_naive();
+    let mut from = start;
+    while from < end {
+        let next = day.succ_opt().unwrap();
+        let to = local_timestamp(next.and_hms_opt(0, 0, 0).unwrap())?.min(end);
+        res.push((day, from, to));
+        day = next;
+        from = to;
+    }
+    Ok(res)
 }
 
 /// show timestamp in hours, minutes, seconds
 pub fn timestamp_to_hms(timestamp: i64) -> String {
     let hours = timestamp / 60 / 60;
-    let minutes = (timestamp - hours * 60) / 60;
+    let minutes = (timestamp - hours * 60 * 60) / 60;
     let seconds = timestamp - hours * 60 * 60 - minutes * 60;
     let mut res = "".to_string();
     if hours > 0 {
@@ -119,7 +487,8 @@ pub fn timestamp() -> i64 {
     Local::now().timestamp()
 }
 
-pub fn split_comma_tags(s: String) -> Vec<String> {
+/// splits a comma separated list and keeps the entries without ~ prefix
+pub fn split_comma_include(s: String) -> Vec<String> {
     if s.is_empty() {
         return vec![];
     }
@@ -132,7 +501,8 @@ pub fn split_comma_tags(s: String) -> Vec<String> {
         .collect()
 }
 
-pub fn split_comma_exclude_tags(s: String) -> Vec<String> {
+/// splits a comma separated list and keeps the entries with ~ prefix, without the ~
+pub fn split_comma_exclude(s: String) -> Vec<String> {
     if s.is_empty() {
         return vec![];
     }
@@ -145,20 +515,6 @@ pub fn split_comma_exclude_tags(s: String) -> Vec<String> {
         .collect()
 }
 
-// return empty vector if input is empty
-// removes illegal characters
-pub fn split_comma_cleanup(s: String) -> Vec<String> {
-    if s.is_empty() {
-        return vec![];
-    }
-    s.split(",")
-        .into_iter()
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
-        .map(|s| remove_illegal_characters(s.to_string()))
-        .collect()
-}
-
 fn remove_illegal_characters(mut s: String) -> String {
     // remove ~ when on start of str
     // because it is used as exclude sign
@@ -280,6 +636,179 @@ pub fn space(s: &str, spacer_len: usize) -> String {
     result
 }
 
+/// splits markdown into the intro and its sections.
+/// Sections start at the highest headings after the first line,
+/// without headings every top-level bullet is a section.
+/// Headings of sections become first level headings, bullet markers are removed
+pub fn split_sections(content: &str) -> (String, Vec<String>) {
+    let lines = content.lines().collect::<Vec<&str>>();
+    let heading_level = |l: &str| {
+        let level = l.chars().take_while(|c| *c == '#').count();
+        if level > 0 && l[level..].starts_with(' ') {
+            Some(level)
+        } else {
+            None
+        }
+    };
+    let is_bullet = |l: &str| l.starts_with("- ") || l.starts_with("* ");
+    // the first line is the title of the item and never a section,
+    // lines in code blocks are no headings
+    let mut in_code_block = false;
+    let top_level = lines
+        .iter()
+        .skip(1)
+        .filter(|l| {
+            if l.starts_with("```") {
+                in_code_block = !in_code_block;
+            }
+            !in_code_block
+        })
+        .filter_map(|l| heading_level(l))
+        .min();
+    let starts_section = |l: &str| match top_level {
+        Some(level) => heading_level(l) == Some(level),
+        None => is_bullet(l),
+    };
+
+    let mut intro = "".to_string();
+    let mut sections: Vec<String> = vec![];
+    let mut in_code_block = false;
+    for (i, line) in lines.iter().enumerate() {
+        if line.starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+        if i > 0 && !in_code_block && starts_section(line) {
+            let title = match top_level {
+                Some(level) => format!("#{}", &line[level..]),
+                None => line[2..].to_string(),
+            };
+            sections.push(title + "\n");
+        } else if top_level.is_some() && !sections.is_empty() {
+            *sections.last_mut().unwrap() += &format!("{}\n", line);
+        } else if !sections.is_empty() && (line.is_empty() || line.starts_with(' ')) {
+            // continuation lines of a bullet are indented,
+            // other lines between the bullets stay in the intro
+            *sections.last_mut().unwrap() +=
+                &format!("{}\n", line.strip_prefix("  ").unwrap_or(line));
+        } else {
+            intro += &format!("{}\n", line);
+        }
+    }
+    (intro, sections)
+}
+
+/// scores how well the characters of the pattern appear in order in the text, ignoring case.
+/// Consecutive characters and matches at word starts score higher.
+/// None if not all characters are found
+pub fn fuzzy_score(pattern: &str, text: &str) -> Option<i64> {
+    let pattern = pattern.to_lowercase().chars().collect::<Vec<char>>();
+    let text = text.to_lowercase().chars().collect::<Vec<char>>();
+    let first = pattern.first()?;
+    // greedy matching from every occurrence of the first character, the best one wins
+    text.iter()
+        .enumerate()
+        .filter(|(_, c)| *c == first)
+        .filter_map(|(start, _)| {
+            let mut score = 0;
+            let mut pos = start;
+            let mut last: Option<usize> = None;
+            for p in &pattern {
+                let i = pos + text[pos..].iter().position(|c| c == p)?;
+                score += 1;
+                if last.is_some_and(|l| l + 1 == i) {
+                    score += 5;
+                }
+                if i == 0 || !text[i - 1].is_alphanumeric() {
+                    score += 3;
+                }
+                last = Some(i);
+                pos = i + 1;
+            }
+            Some(score)
+        })
+        .max()
+}
+
+/// marks all matches of the regex in the text
+pub fn highlight(text: &str, re: Option<&Regex>) -> String {
+    match re {
+        Some(re) => re
+            .replace_all(text, |caps: &regex::Captures| {
+                caps[0].to_string().black().on_yellow().to_string()
+            })
+            .to_string(),
+        None => text.to_string(),
+    }
+}
+
+/// ordered diff of the lines by their longest common subsequence,
+/// removed lines start with - and added ones with +, unchanged lines are left out
+pub fn diff_lines(old: &str, new: &str) -> Vec<String> {
+    let old = old.lines().collect::<Vec<&str>>();
+    let new = new.lines().collect::<Vec<&str>>();
+    // lcs[i][j] is the length of the common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let mut res = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            res.push(format!("- {}", old[i]));
+            i += 1;
+        } else {
+            res.push(format!("+ {}", new[j]));
+            j += 1;
+        }
+    }
+    res
+}
+
+/// box drawing prefixes for the depths of a tree listed in pre-order
+pub fn tree_prefixes(depths: &[usize]) -> Vec<String> {
+    // an entry is the last of its siblings if no entry with the same depth
+    // follows before the parent level is left
+    let is_last = depths
+        .iter()
+        .enumerate()
+        .map(|(i, d)| {
+            depths[i + 1..]
+                .iter()
+                .take_while(|n| *n >= d)
+                .all(|n| n != d)
+        })
+        .collect::<Vec<bool>>();
+    // for every level of the current path, whether more siblings follow
+    let mut open: Vec<bool> = vec![];
+    depths
+        .iter()
+        .enumerate()
+        .map(|(i, d)| {
+            open.truncate(*d);
+            let mut prefix = "".to_string();
+            if *d > 0 {
+                // the root level has no connectors
+                for o in open.iter().skip(1) {
+                    prefix += if *o { "│  " } else { "   " };
+                }
+                prefix += if is_last[i] { "└─ " } else { "├─ " };
+            }
+            open.push(!is_last[i]);
+            prefix
+        })
+        .collect()
+}
+
 /// splits string seperated by comma
 pub fn str_to_vec(s: &str) -> Vec<String> {
     if s.is_empty() {
@@ -288,6 +817,35 @@ pub fn str_to_vec(s: &str) -> Vec<String> {
     s.split(", ").map(|v| v.to_string()).collect()
 }
 
+/// groups tags of the same namespace for display,
+/// e.g. a/b, a/c, d to a/{b, c}, d with the delimiter /
+pub fn group_tags(tags: &[String], delimiter: char) -> Vec<String> {
+    // tags without namespace are kept as groups without rests
+    let mut groups: Vec<(&str, Vec<&str>)> = vec![];
+    for tag in tags {
+        match tag.split_once(delimiter) {
+            Some((namespace, rest)) => {
+                match groups
+                    .iter_mut()
+                    .find(|(n, rests)| *n == namespace && !rests.is_empty())
+                {
+                    Some((_, rests)) => rests.push(rest),
+                    None => groups.push((namespace, vec![rest])),
+                }
+            }
+            None => groups.push((tag, vec![])),
+        }
+    }
+    groups
+        .iter()
+        .map(|(namespace, rests)| match rests.len() {
+            0 => namespace.to_string(),
+            1 => format!("{}{}{}", namespace, delimiter, rests[0]),
+            _ => format!("{}{}{{{}}}", namespace, delimiter, rests.join(", ")),
+        })
+        .collect()
+}
+
 /// joins vector with comma
 pub fn vec_to_str<T>(v: &Vec<T>) -> String
 where
@@ -2,9 +2,10 @@
 use std::error::Error;
 use std::io::{stdout, Write};
 use termimad::crossterm::{
-    cursor::{Hide, Show},
+    cursor::{Hide, MoveTo, Show},
     event::{self, Event, KeyCode::*, KeyEvent},
-    queue,
+    execute, queue,
+    style::{Print, Stylize},
     terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use termimad::terminal_size;
@@ -55,6 +56,60 @@ fn scroll_view(skin: termimad::MadSkin, text: String) -> Result<(), termimad::Er
     Ok(())
 }
 
+/// restores the terminal when the picker ends, also if it fails
+struct PickerGuard;
+
+impl Drop for PickerGuard {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+        let _ = execute!(stdout(), Show, LeaveAlternateScreen);
+    }
+}
+
+/// lets the user choose one of the lines with the arrow keys or j and k,
+/// enter picks the line, every other key aborts with None
+pub fn pick(title: &str, lines: &[String]) -> Result<Option<usize>, Box<dyn Error>> {
+    let mut w = stdout();
+    queue!(w, EnterAlternateScreen)?;
+    let _guard = PickerGuard;
+    terminal::enable_raw_mode()?;
+    queue!(w, Hide)?;
+    let mut selected = 0;
+    let mut offset = 0;
+    let picked = loop {
+        // the title takes the first row
+        let (_, height) = terminal_size();
+        let rows = (height as usize).saturating_sub(1).max(1);
+        if selected < offset {
+            offset = selected;
+        } else if selected >= offset + rows {
+            offset = selected + 1 - rows;
+        }
+        queue!(w, Clear(ClearType::All), MoveTo(0, 0), Print(title.bold()))?;
+        for (i, line) in lines.iter().enumerate().skip(offset).take(rows) {
+            queue!(w, MoveTo(0, (i - offset + 1) as u16))?;
+            if i == selected {
+                queue!(w, Print(format!("> {}", line).reverse()))?;
+            } else {
+                queue!(w, Print(format!("  {}", line)))?;
+            }
+        }
+        w.flush()?;
+        // a failing terminal ends the picker, the guard restores it
+        if let Event::Key(KeyEvent { code, .. }) = event::read()? {
+            match code {
+                Up | Char('k') => selected = selected.saturating_sub(1),
+                Down | Char('j') => selected = (selected + 1).min(lines.len() - 1),
+                Char('g') => selected = 0,
+                Char('G') => selected = lines.len() - 1,
+                Enter => break Some(selected),
+                _ => break None,
+            }
+        }
+    };
+    Ok(picked)
+}
+
 pub fn print(config: &Config, text: String) -> Result<(), Box<dyn Error>> {
     let skin = config.build_skin()?;
     if config.scrollview_threshold > -1